@@ -0,0 +1,22 @@
+extern crate openvr;
+
+use openvr::mock::MockRuntime;
+
+// runs without a vr runtime: cargo run --example mock --features mock
+pub fn main() {
+    let mock = MockRuntime::new()
+        .render_model_names(&["vr_controller_vive_1_5", "lh_basestation_vive"])
+        .component("vr_controller_vive_1_5", "trigger", Some("vr_controller_vive_1_5_trigger"), 1 << 33);
+    let models = mock.render_models();
+
+    assert_eq!(models.get_count(), 2);
+    assert_eq!(models.get_name(0).unwrap(), "vr_controller_vive_1_5");
+    assert_eq!(models.get_name(1).unwrap(), "lh_basestation_vive");
+
+    for name in models.iter_names() {
+        println!("{}", name);
+    }
+
+    let trigger = models.component_name("vr_controller_vive_1_5", 0).unwrap();
+    assert_eq!(models.component_button_mask("vr_controller_vive_1_5", &trigger), 1 << 33);
+}
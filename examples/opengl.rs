@@ -202,7 +202,7 @@ pub fn main() {
 
         'render: loop {
             // this is important to make sure frames are synced correctly
-            let tracked_devices = comp.wait_get_poses();
+            let (tracked_devices, _) = comp.wait_get_poses().unwrap();
 
             let mut left_matrix = left_projection * left_eye_transform;
             let mut right_matrix = right_projection * right_eye_transform;
@@ -281,8 +281,10 @@ pub fn main() {
             target.finish().unwrap();
 
             // submit to hmd
-            comp.submit(openvr::Eye::Left, left_eye_texture.get_id() as usize, openvr::common::TextureBounds::new((0.0, 1.0), (0.0, 1.0)));
-            comp.submit(openvr::Eye::Right, right_eye_texture.get_id() as usize, openvr::common::TextureBounds::new((0.0, 1.0), (0.0, 1.0)));
+            let left_texture = openvr::common::Texture::opengl(left_eye_texture.get_id());
+            comp.submit(openvr::Eye::Left, &left_texture, None, openvr::compositor::SubmitFlags::GL_RENDER_BUFFER).unwrap();
+            let right_texture = openvr::common::Texture::opengl(right_eye_texture.get_id());
+            comp.submit(openvr::Eye::Right, &right_texture, None, openvr::compositor::SubmitFlags::GL_RENDER_BUFFER).unwrap();
 
             // handle window events
             for ev in display.poll_events() {
@@ -86,7 +86,10 @@ fn main() {
 
     println!("\nIVRRenderModels was created\n Count: {}", model.get_count());
     for i in 0..model.get_count() {
-        println!("\t{}", model.get_name(i));
+        match model.get_name(i) {
+            Ok(name) => println!("\t{}", name),
+            Err(err) => println!("\tinvalid name: {:?}", err)
+        }
     }
 
     openvr::shutdown();
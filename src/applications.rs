@@ -0,0 +1,149 @@
+use openvr_sys;
+use openvr_sys::EVRApplicationError::*;
+
+use std::ffi::CString;
+use std::path::Path;
+
+use constants::*;
+use error::*;
+
+/// The applications subsystem, manages the applications known to the runtime
+pub struct IVRApplications(pub *const ());
+
+impl IVRApplications {
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        IVRApplications(ptr as *mut ())
+    }
+
+    /// Registers the applications of a manifest file with the runtime
+    ///  temporary manifests are forgotten when the runtime exits
+    ///  openvr takes utf-8 paths, paths that are not valid utf-8 or contain a nul byte
+    ///  return VRApplicationError_InvalidManifest without calling openvr
+    pub fn add_application_manifest(&self, path: &Path, temporary: bool) -> Result<(), Error<openvr_sys::EVRApplicationError>> {
+        let cpath = match path.to_str().and_then(|p| CString::new(p).ok()) {
+            Some(cpath) => cpath,
+            None => return Err(Error::from_raw(EVRApplicationError_VRApplicationError_InvalidManifest))
+        };
+
+        unsafe {
+            let apps = * { self.0 as *mut openvr_sys::VR_IVRApplications_FnTable };
+            let err = apps.AddApplicationManifest.unwrap()(
+                cpath.as_ptr() as *mut i8,
+                if temporary { 1 } else { 0 }
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns true when an application with the key is registered
+    pub fn is_application_installed(&self, key: &str) -> bool {
+        let ckey = CString::new(key).unwrap();
+
+        unsafe {
+            let apps = * { self.0 as *mut openvr_sys::VR_IVRApplications_FnTable };
+            apps.IsApplicationInstalled.unwrap()(ckey.as_ptr() as *mut i8) > 0
+        }
+    }
+
+    /// Returns the number of registered applications
+    pub fn get_application_count(&self) -> u32 {
+        unsafe {
+            let apps = * { self.0 as *mut openvr_sys::VR_IVRApplications_FnTable };
+            apps.GetApplicationCount.unwrap()()
+        }
+    }
+
+    /// Returns the key of a registered application, index has to be smaller than get_application_count
+    pub fn get_application_key_by_index(&self, index: u32) -> Result<String, Error<openvr_sys::EVRApplicationError>> {
+        let mut buffer: Vec<u8> = vec![0; MAX_APPLICATION_KEY_LENGTH as usize];
+
+        unsafe {
+            let apps = * { self.0 as *mut openvr_sys::VR_IVRApplications_FnTable };
+            let err = apps.GetApplicationKeyByIndex.unwrap()(
+                index,
+                buffer.as_mut_ptr() as *mut i8,
+                MAX_APPLICATION_KEY_LENGTH
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_err() {
+                return Err(error);
+            }
+        }
+
+        let length = buffer.iter().position(|b| *b == 0).unwrap_or(buffer.len());
+        buffer.truncate(length);
+        match String::from_utf8(buffer) {
+            Ok(key) => Ok(key),
+            Err(_) => Err(Error::from_raw(EVRApplicationError_VRApplicationError_InvalidApplication))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::raw::c_char;
+    use stub;
+
+    fn applications(table: &openvr_sys::VR_IVRApplications_FnTable) -> IVRApplications {
+        IVRApplications(stub::table_ptr(table))
+    }
+
+    unsafe extern "C" fn is_application_installed(key: *mut c_char) -> stub::Bool {
+        (stub::read_str(key) == "example.installer") as stub::Bool
+    }
+
+    unsafe extern "C" fn get_application_key_by_index(index: u32, buffer: *mut c_char, len: u32) -> openvr_sys::EVRApplicationError {
+        assert_eq!(len, MAX_APPLICATION_KEY_LENGTH);
+        if index > 0 {
+            return EVRApplicationError_VRApplicationError_InvalidIndex;
+        }
+        stub::write_str("example.installer", buffer, len);
+        EVRApplicationError_VRApplicationError_None
+    }
+
+    #[test]
+    fn install_check_finds_the_known_key() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRApplications_FnTable>();
+        table.IsApplicationInstalled = stub!(is_application_installed);
+        table.GetApplicationKeyByIndex = stub!(get_application_key_by_index);
+        let apps = applications(&table);
+
+        let key = apps.get_application_key_by_index(0).unwrap();
+        assert_eq!(key, "example.installer");
+        assert!(apps.is_application_installed(&key));
+        assert!(!apps.is_application_installed("example.other"));
+
+        let err = apps.get_application_key_by_index(1).unwrap_err();
+        assert_eq!(err.to_raw(), EVRApplicationError_VRApplicationError_InvalidIndex);
+    }
+
+    #[test]
+    fn manifest_paths_with_a_nul_are_rejected() {
+        // the path is checked before openvr is called, so no runtime is needed
+        let apps = unsafe { IVRApplications::from_raw(::std::ptr::null()) };
+
+        let err = apps.add_application_manifest(Path::new("manifests/\0example.vrmanifest"), true).unwrap_err();
+        assert_eq!(err.to_raw(), EVRApplicationError_VRApplicationError_InvalidManifest);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn manifest_paths_with_invalid_utf8_are_rejected() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let apps = unsafe { IVRApplications::from_raw(::std::ptr::null()) };
+        let path = Path::new(OsStr::from_bytes(b"manifests/\xffexample.vrmanifest"));
+
+        let err = apps.add_application_manifest(path, false).unwrap_err();
+        assert_eq!(err.to_raw(), EVRApplicationError_VRApplicationError_InvalidManifest);
+    }
+}
@@ -0,0 +1,245 @@
+use openvr_sys;
+
+/// Describes the calibration of the chaperone
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChaperoneCalibrationState {
+    /// the chaperone is fully calibrated and working
+    Success,
+
+    Warning,
+    /// a base station was moved, the chaperone may be offset
+    WarningBaseStationMayHaveMoved,
+    /// a base station was removed, the remaining ones still work
+    WarningBaseStationRemoved,
+    WarningSeatedBoundsInvalid,
+
+    /// tracking center is not calibrated
+    Error,
+    ErrorBaseStationUninitialized,
+    /// the tracking center was calibrated with other base stations
+    ErrorBaseStationConflict,
+    ErrorPlayAreaInvalid,
+    ErrorCollisionBoundsInvalid,
+}
+
+impl ChaperoneCalibrationState {
+    pub fn from_raw(raw: openvr_sys::ChaperoneCalibrationState) -> Self {
+        use self::ChaperoneCalibrationState::*;
+        use openvr_sys::ChaperoneCalibrationState::*;
+
+        match raw {
+            ChaperoneCalibrationState_ChaperoneCalibrationState_OK => Success,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Warning => Warning,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Warning_BaseStationMayHaveMoved => WarningBaseStationMayHaveMoved,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Warning_BaseStationRemoved => WarningBaseStationRemoved,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Warning_SeatedBoundsInvalid => WarningSeatedBoundsInvalid,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Error => Error,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Error_BaseStationUninitialized => ErrorBaseStationUninitialized,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Error_BaseStationConflict => ErrorBaseStationConflict,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Error_PlayAreaInvalid => ErrorPlayAreaInvalid,
+            ChaperoneCalibrationState_ChaperoneCalibrationState_Error_CollisionBoundsInvalid => ErrorCollisionBoundsInvalid,
+        }
+    }
+
+    /// Returns true when the play area can be used, possibly with warnings
+    pub fn is_usable(&self) -> bool {
+        !self.is_error()
+    }
+
+    /// Returns true when the user should be asked to recalibrate
+    pub fn is_warning(&self) -> bool {
+        use self::ChaperoneCalibrationState::*;
+
+        match *self {
+            Warning | WarningBaseStationMayHaveMoved | WarningBaseStationRemoved | WarningSeatedBoundsInvalid => true,
+            _ => false
+        }
+    }
+
+    /// Returns true when the play area data can not be trusted
+    pub fn is_error(&self) -> bool {
+        use self::ChaperoneCalibrationState::*;
+
+        match *self {
+            Error | ErrorBaseStationUninitialized | ErrorBaseStationConflict |
+            ErrorPlayAreaInvalid | ErrorCollisionBoundsInvalid => true,
+            _ => false
+        }
+    }
+}
+
+/// The chaperone, describes the physical bounds of the play area
+pub struct IVRChaperone(*const ());
+
+impl IVRChaperone {
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        IVRChaperone(ptr as *mut ())
+    }
+
+    /// Get the current calibration state, check before trusting the play area
+    pub fn calibration_state(&self) -> ChaperoneCalibrationState {
+        unsafe {
+            let chaperone = * { self.0 as *mut openvr_sys::VR_IVRChaperone_FnTable };
+            ChaperoneCalibrationState::from_raw(chaperone.GetCalibrationState.unwrap()())
+        }
+    }
+
+    /// Get the size of the play area in meters, as (x, z)
+    ///  None when the chaperone is not calibrated
+    pub fn play_area_size(&self) -> Option<(f32, f32)> {
+        unsafe {
+            let chaperone = * { self.0 as *mut openvr_sys::VR_IVRChaperone_FnTable };
+            let mut x = 0.0;
+            let mut z = 0.0;
+            let found = chaperone.GetPlayAreaSize.unwrap()(
+                &mut x,
+                &mut z
+            );
+
+            if found > 0 {
+                Some((x, z))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Get the 4 corners of the play area in the standing tracking space
+    ///  the corners lie on the floor (y is 0)
+    ///  None when the chaperone is not calibrated
+    pub fn play_area_rect(&self) -> Option<[[f32; 3]; 4]> {
+        use std;
+
+        unsafe {
+            let chaperone = * { self.0 as *mut openvr_sys::VR_IVRChaperone_FnTable };
+            let mut quad: openvr_sys::HmdQuad_t = std::mem::zeroed();
+            let found = chaperone.GetPlayAreaRect.unwrap()(
+                &mut quad
+            );
+
+            if found > 0 {
+                let mut corners = [[0.0; 3]; 4];
+                for (i, corner) in quad.vCorners.iter().enumerate() {
+                    corners[i] = corner.v;
+                }
+                Some(corners)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns true when the chaperone bounds are currently shown
+    pub fn bounds_visible(&self) -> bool {
+        unsafe {
+            let chaperone = * { self.0 as *mut openvr_sys::VR_IVRChaperone_FnTable };
+            chaperone.AreBoundsVisible.unwrap()() > 0
+        }
+    }
+
+    /// Forces the chaperone bounds to be shown, until it is called again with false
+    pub fn force_bounds_visible(&self, visible: bool) {
+        unsafe {
+            let chaperone = * { self.0 as *mut openvr_sys::VR_IVRChaperone_FnTable };
+            chaperone.ForceBoundsVisible.unwrap()(if visible { 1 } else { 0 });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openvr_sys::ChaperoneCalibrationState::*;
+    use std::cell::Cell;
+    use stub;
+
+    fn chaperone(table: &openvr_sys::VR_IVRChaperone_FnTable) -> IVRChaperone {
+        IVRChaperone(stub::table_ptr(table))
+    }
+
+    #[test]
+    fn from_raw_maps_ok_to_success() {
+        assert_eq!(ChaperoneCalibrationState::from_raw(ChaperoneCalibrationState_ChaperoneCalibrationState_OK), ChaperoneCalibrationState::Success);
+        assert_eq!(ChaperoneCalibrationState::from_raw(ChaperoneCalibrationState_ChaperoneCalibrationState_Error_PlayAreaInvalid), ChaperoneCalibrationState::ErrorPlayAreaInvalid);
+    }
+
+    #[test]
+    fn state_predicates() {
+        let success = ChaperoneCalibrationState::Success;
+        assert!(success.is_usable() && !success.is_warning() && !success.is_error());
+
+        let warning = ChaperoneCalibrationState::WarningBaseStationMayHaveMoved;
+        assert!(warning.is_usable() && warning.is_warning() && !warning.is_error());
+
+        let error = ChaperoneCalibrationState::ErrorBaseStationConflict;
+        assert!(!error.is_usable() && !error.is_warning() && error.is_error());
+    }
+
+    thread_local! {
+        static CALIBRATED: Cell<bool> = Cell::new(true);
+    }
+
+    unsafe extern "C" fn get_play_area_size(x: *mut f32, z: *mut f32) -> stub::Bool {
+        if !CALIBRATED.with(|calibrated| calibrated.get()) {
+            return 0;
+        }
+        *x = 2.5;
+        *z = 1.5;
+        1
+    }
+
+    unsafe extern "C" fn get_play_area_rect(quad: *mut openvr_sys::HmdQuad_t) -> stub::Bool {
+        if !CALIBRATED.with(|calibrated| calibrated.get()) {
+            return 0;
+        }
+        let corners = [[-1.25, 0.0, -0.75], [1.25, 0.0, -0.75], [1.25, 0.0, 0.75], [-1.25, 0.0, 0.75]];
+        for (corner, v) in (*quad).vCorners.iter_mut().zip(corners.iter()) {
+            corner.v = *v;
+        }
+        1
+    }
+
+    #[test]
+    fn play_area_is_read_when_calibrated() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRChaperone_FnTable>();
+        table.GetPlayAreaSize = stub!(get_play_area_size);
+        table.GetPlayAreaRect = stub!(get_play_area_rect);
+        let chaperone = chaperone(&table);
+
+        CALIBRATED.with(|calibrated| calibrated.set(true));
+        assert_eq!(chaperone.play_area_size(), Some((2.5, 1.5)));
+        assert_eq!(chaperone.play_area_rect(), Some([[-1.25, 0.0, -0.75], [1.25, 0.0, -0.75], [1.25, 0.0, 0.75], [-1.25, 0.0, 0.75]]));
+
+        CALIBRATED.with(|calibrated| calibrated.set(false));
+        assert_eq!(chaperone.play_area_size(), None);
+        assert_eq!(chaperone.play_area_rect(), None);
+    }
+
+    thread_local! {
+        static BOUNDS_VISIBLE: Cell<bool> = Cell::new(false);
+    }
+
+    unsafe extern "C" fn are_bounds_visible() -> stub::Bool {
+        BOUNDS_VISIBLE.with(|visible| visible.get()) as stub::Bool
+    }
+
+    unsafe extern "C" fn force_bounds_visible(force: stub::Bool) {
+        stub::record(format!("ForceBoundsVisible {}", force));
+        BOUNDS_VISIBLE.with(|visible| visible.set(force > 0));
+    }
+
+    #[test]
+    fn bounds_visibility_passes_through() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRChaperone_FnTable>();
+        table.AreBoundsVisible = stub!(are_bounds_visible);
+        table.ForceBoundsVisible = stub!(force_bounds_visible);
+        let chaperone = chaperone(&table);
+
+        assert!(!chaperone.bounds_visible());
+        chaperone.force_bounds_visible(true);
+        assert!(chaperone.bounds_visible());
+        chaperone.force_bounds_visible(false);
+        assert!(!chaperone.bounds_visible());
+        assert_eq!(stub::take_calls(), vec!["ForceBoundsVisible 1", "ForceBoundsVisible 0"]);
+    }
+}
@@ -0,0 +1,171 @@
+use openvr_sys;
+
+/// Describes where a committed working copy is stored
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChaperoneConfigFile {
+    /// the chaperone config used by all applications
+    Live,
+    /// a temporary config, discarded when the runtime exits
+    Temp,
+}
+
+impl ChaperoneConfigFile {
+    pub fn to_raw(&self) -> openvr_sys::EChaperoneConfigFile {
+        use openvr_sys::EChaperoneConfigFile::*;
+
+        match *self {
+            ChaperoneConfigFile::Live => EChaperoneConfigFile_EChaperoneConfigFile_Live,
+            ChaperoneConfigFile::Temp => EChaperoneConfigFile_EChaperoneConfigFile_Temp,
+        }
+    }
+}
+
+/// The chaperone setup, used by room setup tools to edit the bounds
+///  all changes apply to a working copy until it is committed
+pub struct IVRChaperoneSetup(*const ());
+
+impl IVRChaperoneSetup {
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        IVRChaperoneSetup(ptr as *mut ())
+    }
+
+    /// Saves the working copy, returns false when it could not be written
+    pub fn commit_working_copy(&self, config: ChaperoneConfigFile) -> bool {
+        unsafe {
+            let setup = * { self.0 as *mut openvr_sys::VR_IVRChaperoneSetup_FnTable };
+            setup.CommitWorkingCopy.unwrap()(config.to_raw()) > 0
+        }
+    }
+
+    /// Discards the working copy and reloads it from the live config
+    pub fn revert_working_copy(&self) {
+        unsafe {
+            let setup = * { self.0 as *mut openvr_sys::VR_IVRChaperoneSetup_FnTable };
+            setup.RevertWorkingCopy.unwrap()();
+        }
+    }
+
+    /// Get the size of the play area of the working copy in meters, as (x, z)
+    ///  None when the working copy has no play area
+    pub fn get_working_play_area_size(&self) -> Option<(f32, f32)> {
+        unsafe {
+            let setup = * { self.0 as *mut openvr_sys::VR_IVRChaperoneSetup_FnTable };
+            let mut x = 0.0;
+            let mut z = 0.0;
+            let found = setup.GetWorkingPlayAreaSize.unwrap()(
+                &mut x,
+                &mut z
+            );
+
+            if found > 0 {
+                Some((x, z))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Sets the size of the play area of the working copy in meters
+    pub fn set_working_play_area_size(&self, x: f32, z: f32) {
+        unsafe {
+            let setup = * { self.0 as *mut openvr_sys::VR_IVRChaperoneSetup_FnTable };
+            setup.SetWorkingPlayAreaSize.unwrap()(x, z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use stub;
+
+    fn setup(table: &openvr_sys::VR_IVRChaperoneSetup_FnTable) -> IVRChaperoneSetup {
+        IVRChaperoneSetup(stub::table_ptr(table))
+    }
+
+    // the working copy and the live config of the stub, the temp config is not kept
+    thread_local! {
+        static WORKING: Cell<Option<(f32, f32)>> = Cell::new(None);
+        static LIVE: Cell<Option<(f32, f32)>> = Cell::new(None);
+    }
+
+    unsafe extern "C" fn commit_working_copy(config: openvr_sys::EChaperoneConfigFile) -> stub::Bool {
+        let live = config == ChaperoneConfigFile::Live.to_raw();
+        stub::record(if live { "CommitWorkingCopy Live" } else { "CommitWorkingCopy Temp" });
+        if live {
+            LIVE.with(|l| l.set(WORKING.with(|w| w.get())));
+        }
+        1
+    }
+
+    unsafe extern "C" fn revert_working_copy() {
+        stub::record("RevertWorkingCopy");
+        WORKING.with(|w| w.set(LIVE.with(|l| l.get())));
+    }
+
+    unsafe extern "C" fn get_working_play_area_size(x: *mut f32, z: *mut f32) -> stub::Bool {
+        match WORKING.with(|w| w.get()) {
+            Some(size) => {
+                *x = size.0;
+                *z = size.1;
+                1
+            },
+            None => 0
+        }
+    }
+
+    unsafe extern "C" fn set_working_play_area_size(x: f32, z: f32) {
+        stub::record(format!("SetWorkingPlayAreaSize {} {}", x, z));
+        WORKING.with(|w| w.set(Some((x, z))));
+    }
+
+    fn table() -> openvr_sys::VR_IVRChaperoneSetup_FnTable {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRChaperoneSetup_FnTable>();
+        table.CommitWorkingCopy = stub!(commit_working_copy);
+        table.RevertWorkingCopy = stub!(revert_working_copy);
+        table.GetWorkingPlayAreaSize = stub!(get_working_play_area_size);
+        table.SetWorkingPlayAreaSize = stub!(set_working_play_area_size);
+        WORKING.with(|w| w.set(None));
+        LIVE.with(|l| l.set(Some((2.0, 2.0))));
+        table
+    }
+
+    #[test]
+    fn working_size_reads_back() {
+        let table = table();
+        let setup = setup(&table);
+
+        assert_eq!(setup.get_working_play_area_size(), None);
+        setup.set_working_play_area_size(3.5, 2.25);
+        assert_eq!(setup.get_working_play_area_size(), Some((3.5, 2.25)));
+    }
+
+    #[test]
+    fn revert_restores_the_last_live_commit() {
+        let table = table();
+        let setup = setup(&table);
+
+        // a temp commit leaves the live config alone
+        setup.set_working_play_area_size(3.0, 2.0);
+        assert!(setup.commit_working_copy(ChaperoneConfigFile::Temp));
+        setup.revert_working_copy();
+        assert_eq!(setup.get_working_play_area_size(), Some((2.0, 2.0)));
+
+        setup.set_working_play_area_size(4.0, 3.0);
+        assert!(setup.commit_working_copy(ChaperoneConfigFile::Live));
+        setup.set_working_play_area_size(1.0, 1.0);
+        setup.revert_working_copy();
+        assert_eq!(setup.get_working_play_area_size(), Some((4.0, 3.0)));
+
+        assert_eq!(stub::take_calls(), vec![
+            "SetWorkingPlayAreaSize 3 2",
+            "CommitWorkingCopy Temp",
+            "RevertWorkingCopy",
+            "SetWorkingPlayAreaSize 4 3",
+            "CommitWorkingCopy Live",
+            "SetWorkingPlayAreaSize 1 1",
+            "RevertWorkingCopy",
+        ]);
+    }
+}
@@ -1,32 +1,50 @@
 use openvr_sys;
 use openvr_sys::EVREye::*;
 
+use std::os::raw::c_void;
+use std::ptr::null_mut;
+
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Size {
     pub width: u32,
     pub height: u32
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     pub x: i32,
     pub y: i32
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rectangle {
     pub position: Position,
     pub size: Size
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DistortionCoordinates {
     pub red: [f32; 2],
     pub green: [f32; 2],
     pub blue: [f32; 2],
 }
 
+/// The tangents of the half angles of an eye frustum, see IVRSystem::projection_raw
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProjectionRaw {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Eye {
     Left, Right
 }
@@ -41,7 +59,43 @@ impl Eye {
     }
 }
 
+/// Describes how an application uses openvr, see Context::init
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApplicationType {
+    /// some other kind of application that isn't covered by the other types
+    Other,
+    /// a 3d application that renders through the compositor
+    Scene,
+    /// an application that only interacts with the runtime through overlays
+    Overlay,
+    /// accesses the runtime without starting vrserver, fails when it is not running
+    Background,
+    /// for tools that only need the settings and infos of the runtime
+    Utility
+}
+
+impl ApplicationType {
+    pub fn to_raw(&self) -> openvr_sys::EVRApplicationType {
+        use openvr_sys::EVRApplicationType::*;
+
+        match *self {
+            ApplicationType::Other => EVRApplicationType_VRApplication_Other,
+            ApplicationType::Scene => EVRApplicationType_VRApplication_Scene,
+            ApplicationType::Overlay => EVRApplicationType_VRApplication_Overlay,
+            ApplicationType::Background => EVRApplicationType_VRApplication_Background,
+            ApplicationType::Utility => EVRApplicationType_VRApplication_Utility,
+        }
+    }
+}
+
+impl From<ApplicationType> for openvr_sys::EVRApplicationType {
+    fn from(application_type: ApplicationType) -> Self {
+        application_type.to_raw()
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TextureBounds {
     pub u_min: f32,
     pub u_max: f32,
@@ -78,3 +132,167 @@ impl TextureBounds {
         }
     }
 }
+
+/// Describes the graphics api that created a texture
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureType {
+    OpenGL,
+    DirectX
+}
+
+impl TextureType {
+    pub fn to_raw(&self) -> openvr_sys::EGraphicsAPIConvention {
+        use openvr_sys::EGraphicsAPIConvention::*;
+
+        match *self {
+            TextureType::OpenGL => EGraphicsAPIConvention_API_OpenGL,
+            TextureType::DirectX => EGraphicsAPIConvention_API_DirectX,
+        }
+    }
+}
+
+/// Describes how the colors of a texture are encoded
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// let the compositor decide, 8 bit textures are treated as gamma
+    Auto,
+    Gamma,
+    Linear
+}
+
+impl ColorSpace {
+    pub fn to_raw(&self) -> openvr_sys::EColorSpace {
+        use openvr_sys::EColorSpace::*;
+
+        match *self {
+            ColorSpace::Auto => EColorSpace_ColorSpace_Auto,
+            ColorSpace::Gamma => EColorSpace_ColorSpace_Gamma,
+            ColorSpace::Linear => EColorSpace_ColorSpace_Linear,
+        }
+    }
+}
+
+/// A texture handle that can be submitted to the compositor
+///  for opengl the handle is the texture (or render buffer) id, for directx the ID3D11Texture2D pointer
+#[derive(Debug, Copy, Clone)]
+pub struct Texture {
+    pub handle: *mut c_void,
+    pub texture_type: TextureType,
+    pub color_space: ColorSpace
+}
+
+impl Texture {
+    /// Creates a texture from an opengl texture or render buffer id
+    pub fn opengl(id: u32) -> Self {
+        Texture {
+            handle: id as usize as *mut c_void,
+            texture_type: TextureType::OpenGL,
+            color_space: ColorSpace::Auto
+        }
+    }
+
+    /// Creates a texture from an ID3D11Texture2D pointer
+    pub fn directx(ptr: *mut c_void) -> Self {
+        Texture {
+            handle: ptr,
+            texture_type: TextureType::DirectX,
+            color_space: ColorSpace::Auto
+        }
+    }
+
+    /// Replaces the color space, the constructors default to ColorSpace::Auto
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Convert a texture to a openvr texture
+    pub fn to_raw(&self) -> openvr_sys::Texture_t {
+        openvr_sys::Texture_t {
+            handle: self.handle,
+            eType: self.texture_type.to_raw(),
+            eColorSpace: self.color_space.to_raw(),
+        }
+    }
+}
+
+/// Why a string could not be read with read_sized_string
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ReadStringError {
+    /// the size changed between the sizing call and the read
+    SizeChanged,
+    /// the required size is above the limit
+    TooLong,
+    /// the string is not valid utf-8
+    InvalidUtf8
+}
+
+/// Reads a string from openvr using the two call sizing pattern
+///  the first call with an empty buffer returns the required size (including the terminator),
+///  the second call fills the allocated buffer, nothing is read for a size of 0
+///  all strings of the crate are read this way, so invalid utf-8 is always rejected
+pub(crate) fn read_sized_string<F>(max_size: Option<u32>, mut fetch: F) -> Result<String, ReadStringError>
+    where F: FnMut(*mut i8, u32) -> u32
+{
+    let required = fetch(null_mut(), 0);
+    if required == 0 {
+        return Ok(String::new());
+    }
+    if max_size.map_or(false, |max| required > max) {
+        return Err(ReadStringError::TooLong);
+    }
+
+    let mut buffer: Vec<u8> = vec![0; required as usize];
+    let size = fetch(buffer.as_mut_ptr() as *mut i8, required);
+    if size != required {
+        return Err(ReadStringError::SizeChanged);
+    }
+    buffer.truncate(size as usize - 1);
+
+    String::from_utf8(buffer).map_err(|_| ReadStringError::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openvr_sys::EColorSpace::*;
+    use openvr_sys::EGraphicsAPIConvention::*;
+
+    #[test]
+    fn textures_convert_to_the_raw_type_and_color_space() {
+        let opengl = Texture::opengl(7).to_raw();
+        assert_eq!(opengl.handle as usize, 7);
+        assert_eq!(opengl.eType, EGraphicsAPIConvention_API_OpenGL);
+        assert_eq!(opengl.eColorSpace, EColorSpace_ColorSpace_Auto);
+
+        let mut d3d_texture = 0u8;
+        let pointer = &mut d3d_texture as *mut u8 as *mut c_void;
+        let directx = Texture::directx(pointer).to_raw();
+        assert_eq!(directx.handle, pointer);
+        assert_eq!(directx.eType, EGraphicsAPIConvention_API_DirectX);
+        assert_eq!(directx.eColorSpace, EColorSpace_ColorSpace_Auto);
+
+        let linear = Texture::opengl(7).with_color_space(ColorSpace::Linear).to_raw();
+        assert_eq!(linear.eType, EGraphicsAPIConvention_API_OpenGL);
+        assert_eq!(linear.eColorSpace, EColorSpace_ColorSpace_Linear);
+        assert_eq!(Texture::directx(pointer).with_color_space(ColorSpace::Gamma).to_raw().eColorSpace, EColorSpace_ColorSpace_Gamma);
+    }
+
+    #[test]
+    fn application_types_convert_to_their_raw_value() {
+        use openvr_sys::EVRApplicationType::*;
+
+        let cases = [
+            (ApplicationType::Other, EVRApplicationType_VRApplication_Other),
+            (ApplicationType::Scene, EVRApplicationType_VRApplication_Scene),
+            (ApplicationType::Overlay, EVRApplicationType_VRApplication_Overlay),
+            (ApplicationType::Background, EVRApplicationType_VRApplication_Background),
+            (ApplicationType::Utility, EVRApplicationType_VRApplication_Utility),
+        ];
+
+        for &(application_type, raw) in cases.iter() {
+            assert_eq!(application_type.to_raw(), raw);
+            assert_eq!(openvr_sys::EVRApplicationType::from(application_type), raw);
+        }
+    }
+}
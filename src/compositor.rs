@@ -1,9 +1,141 @@
 use openvr_sys;
-use openvr_sys::EGraphicsAPIConvention::*;
 use openvr_sys::EVRSubmitFlags::*;
-use openvr_sys::EColorSpace::*;
+use openvr_sys::EVRCompositorError::*;
+use std::ops;
 use common::*;
 use tracking::*;
+use error::*;
+use constants::*;
+
+/// Describes how a texture is submitted to the compositor
+///  the flags are bits of EVRSubmitFlags and can be combined with |
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SubmitFlags(u32);
+
+impl SubmitFlags {
+    /// no special handling of the texture
+    pub const DEFAULT: SubmitFlags = SubmitFlags(EVRSubmitFlags_Submit_Default as u32);
+    /// the texture is already distorted, the compositor will skip the lens distortion
+    pub const LENS_DISTORTION_ALREADY_APPLIED: SubmitFlags = SubmitFlags(EVRSubmitFlags_Submit_LensDistortionAlreadyApplied as u32);
+    /// the opengl handle is a render buffer instead of a texture
+    pub const GL_RENDER_BUFFER: SubmitFlags = SubmitFlags(EVRSubmitFlags_Submit_GlRenderBuffer as u32);
+
+    /// Returns the raw bits as passed to openvr
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true when all flags of other are set
+    pub fn contains(self, other: SubmitFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for SubmitFlags {
+    type Output = SubmitFlags;
+
+    fn bitor(self, other: SubmitFlags) -> SubmitFlags {
+        SubmitFlags(self.0 | other.0)
+    }
+}
+
+impl ops::BitOrAssign for SubmitFlags {
+    fn bitor_assign(&mut self, other: SubmitFlags) {
+        self.0 |= other.0;
+    }
+}
+
+/// Timing information of a single frame, see IVRCompositor::frame_timing
+#[derive(Debug, Copy, Clone)]
+pub struct FrameTiming {
+    pub frame_index: u32,
+    /// number of times the frame was presented
+    pub num_frame_presents: u32,
+    /// number of times the frame was presented before the next one was ready
+    pub num_dropped_frames: u32,
+    pub system_time_seconds: f64,
+    /// gpu time of the application rendering the scene
+    pub scene_render_gpu_ms: f32,
+    /// gpu time of the application and the compositor together
+    pub total_render_gpu_ms: f32,
+    pub compositor_render_gpu_ms: f32,
+    pub compositor_render_cpu_ms: f32,
+    pub compositor_idle_cpu_ms: f32,
+    /// time between the submits of the application
+    pub client_frame_interval_ms: f32,
+    pub present_call_cpu_ms: f32,
+    pub wait_for_present_cpu_ms: f32,
+    pub submit_frame_ms: f32,
+}
+
+impl FrameTiming {
+    pub fn from_raw(raw: &openvr_sys::Compositor_FrameTiming) -> Self {
+        FrameTiming {
+            frame_index: raw.m_nFrameIndex,
+            num_frame_presents: raw.m_nNumFramePresents,
+            num_dropped_frames: raw.m_nNumDroppedFrames,
+            system_time_seconds: raw.m_flSystemTimeInSeconds,
+            scene_render_gpu_ms: raw.m_flSceneRenderGpuMs,
+            total_render_gpu_ms: raw.m_flTotalRenderGpuMs,
+            compositor_render_gpu_ms: raw.m_flCompositorRenderGpuMs,
+            compositor_render_cpu_ms: raw.m_flCompositorRenderCpuMs,
+            compositor_idle_cpu_ms: raw.m_flCompositorIdleCpuMs,
+            client_frame_interval_ms: raw.m_flClientFrameIntervalMs,
+            present_call_cpu_ms: raw.m_flPresentCallCpuMs,
+            wait_for_present_cpu_ms: raw.m_flWaitForPresentCpuMs,
+            submit_frame_ms: raw.m_flSubmitFrameMs,
+        }
+    }
+}
+
+/// Frame statistics of the whole session, see IVRCompositor::cumulative_stats
+#[derive(Debug, Copy, Clone)]
+pub struct CompositorCumulativeStats {
+    /// process id of the application the stats belong to
+    pub pid: u32,
+    pub num_frame_presents: u32,
+    pub num_dropped_frames: u32,
+    pub num_reprojected_frames: u32,
+
+    /// frames while the application was starting
+    pub num_frame_presents_on_startup: u32,
+    pub num_dropped_frames_on_startup: u32,
+    pub num_reprojected_frames_on_startup: u32,
+
+    /// number of times the application was loading, and the frames during loading
+    pub num_loading: u32,
+    pub num_frame_presents_loading: u32,
+    pub num_dropped_frames_loading: u32,
+    pub num_reprojected_frames_loading: u32,
+
+    /// number of times the application timed out, and the frames during the time outs
+    pub num_timed_out: u32,
+    pub num_frame_presents_timed_out: u32,
+    pub num_dropped_frames_timed_out: u32,
+    pub num_reprojected_frames_timed_out: u32,
+}
+
+impl CompositorCumulativeStats {
+    pub fn from_raw(raw: &openvr_sys::Compositor_CumulativeStats) -> Self {
+        CompositorCumulativeStats {
+            pid: raw.m_nPid,
+            num_frame_presents: raw.m_nNumFramePresents,
+            num_dropped_frames: raw.m_nNumDroppedFrames,
+            num_reprojected_frames: raw.m_nNumReprojectedFrames,
+            num_frame_presents_on_startup: raw.m_nNumFramePresentsOnStartup,
+            num_dropped_frames_on_startup: raw.m_nNumDroppedFramesOnStartup,
+            num_reprojected_frames_on_startup: raw.m_nNumReprojectedFramesOnStartup,
+            num_loading: raw.m_nNumLoading,
+            num_frame_presents_loading: raw.m_nNumFramePresentsLoading,
+            num_dropped_frames_loading: raw.m_nNumDroppedFramesLoading,
+            num_reprojected_frames_loading: raw.m_nNumReprojectedFramesLoading,
+            num_timed_out: raw.m_nNumTimedOut,
+            num_frame_presents_timed_out: raw.m_nNumFramePresentsTimedOut,
+            num_dropped_frames_timed_out: raw.m_nNumDroppedFramesTimedOut,
+            num_reprojected_frames_timed_out: raw.m_nNumReprojectedFramesTimedOut,
+        }
+    }
+}
 
 /// A VR compositor
 pub struct IVRCompositor(*const ());
@@ -29,45 +161,588 @@ impl IVRCompositor {
         }
     }
 
-    /// Submits an opengl framebuffer as an eye to the render
-    pub fn submit(&self, eye: Eye, texture: usize, bounds: TextureBounds) {
-        let mut b = bounds.to_raw();
-        let e = eye.to_raw();
+    /// Submits the texture of an eye to the compositor
+    ///  without bounds the full texture is used
+    ///  opengl render buffers have to be submitted with SubmitFlags::GL_RENDER_BUFFER
+    pub fn submit(&self, eye: Eye, texture: &Texture, bounds: Option<TextureBounds>, flags: SubmitFlags) -> Result<(), Error<openvr_sys::EVRCompositorError>> {
+        use std;
 
-        unsafe {
-            use std;
+        let mut t = texture.to_raw();
+        let mut b = bounds.map(|b| b.to_raw());
 
+        unsafe {
             let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
-            let mut t = openvr_sys::Texture_t {
-                eType: EGraphicsAPIConvention_API_OpenGL,
-                eColorSpace: EColorSpace_ColorSpace_Auto,
-                handle: texture as *mut std::os::raw::c_void,
-            };
 
-            comp.Submit.unwrap()(
-                e,
+            // openvr takes the flags as a bit field, combined flags are no valid EVRSubmitFlags value
+            //  so the entry is called with a plain u32, which has the same abi as the enum
+            let submit: unsafe extern "C" fn(
+                openvr_sys::EVREye,
+                *mut openvr_sys::Texture_t,
+                *mut openvr_sys::VRTextureBounds_t,
+                u32
+            ) -> openvr_sys::EVRCompositorError = std::mem::transmute(comp.Submit.unwrap());
+
+            let err = submit(
+                eye.to_raw(),
                 &mut t,
-                &mut b as *mut openvr_sys::VRTextureBounds_t,
-                EVRSubmitFlags_Submit_GlRenderBuffer
+                match b {
+                    Some(ref mut b) => b as *mut openvr_sys::VRTextureBounds_t,
+                    None => std::ptr::null_mut()
+                },
+                flags.bits()
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Fades the view to the given rgba color over the given time
+    ///  background fades the scene application instead of the overlays on top of it
+    ///  fade back in by fading to a color with an alpha of 0
+    pub fn fade_to_color(&self, seconds: f32, color: [f32; 4], background: bool) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.FadeToColor.unwrap()(
+                seconds,
+                color[0], color[1], color[2], color[3],
+                if background { 1 } else { 0 }
+            );
+        }
+    }
+
+    /// Fades between the scene and the compositor grid over the given time
+    ///  fade_in shows the grid, used while the application loads
+    pub fn fade_grid(&self, seconds: f32, fade_in: bool) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.FadeGrid.unwrap()(
+                seconds,
+                if fade_in { 1 } else { 0 }
+            );
+        }
+    }
+
+    /// Returns the timing of a previous frame, 0 is the current frame
+    ///  None when no timing is available for the frame
+    pub fn frame_timing(&self, frames_ago: u32) -> Option<FrameTiming> {
+        use std;
+
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            let mut timing: openvr_sys::Compositor_FrameTiming = std::mem::zeroed();
+            timing.m_nSize = std::mem::size_of::<openvr_sys::Compositor_FrameTiming>() as u32;
+
+            let found = comp.GetFrameTiming.unwrap()(
+                &mut timing,
+                frames_ago
             );
+
+            if found > 0 {
+                Some(FrameTiming::from_raw(&timing))
+            } else {
+                None
+            }
         }
     }
 
-    /// Get the poses
-    pub fn wait_get_poses(&self) -> TrackedDevicePoses {
+    /// Replaces the scene with a skybox, for example while loading
+    ///  accepts 1 texture (latitude-longitude), 2 textures (stereo latitude-longitude, left and right)
+    ///  or 6 textures (cube map faces: front, back, left, right, top, bottom)
+    ///  any other count returns the VRCompositorError_InvalidTexture error
+    pub fn set_skybox(&self, textures: &[Texture]) -> Result<(), Error<openvr_sys::EVRCompositorError>> {
+        match textures.len() {
+            1 | 2 | 6 => {},
+            _ => {
+                return Err(Error::from_raw(EVRCompositorError_VRCompositorError_InvalidTexture));
+            }
+        }
+
+        let mut raw: Vec<openvr_sys::Texture_t> = textures.iter().map(|t| t.to_raw()).collect();
+
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            let err = comp.SetSkyboxOverride.unwrap()(
+                raw.as_mut_ptr(),
+                raw.len() as u32
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Removes the skybox set by set_skybox
+    pub fn clear_skybox_override(&self) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.ClearSkyboxOverride.unwrap()();
+        }
+    }
+
+    /// Returns the frame statistics of the current session
+    pub fn cumulative_stats(&self) -> CompositorCumulativeStats {
         use std;
 
         unsafe {
             let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
-            let mut data: [openvr_sys::TrackedDevicePose_t; 16] = std::mem::zeroed();
+            let mut stats: openvr_sys::Compositor_CumulativeStats = std::mem::zeroed();
 
-            comp.WaitGetPoses.unwrap()(
-                &mut data[0],
-                16,
-                std::ptr::null_mut(),
-                0
+            comp.GetCumulativeStats.unwrap()(
+                &mut stats,
+                std::mem::size_of::<openvr_sys::Compositor_CumulativeStats>() as u32
             );
-            to_tracked(data)
+            CompositorCumulativeStats::from_raw(&stats)
+        }
+    }
+
+    /// Sets the tracking space the poses of wait_get_poses are relative to
+    pub fn set_tracking_space(&self, origin: TrackingUniverseOrigin) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.SetTrackingSpace.unwrap()(origin.to_raw());
         }
     }
+
+    /// Gets the tracking space the poses of wait_get_poses are relative to
+    pub fn tracking_space(&self) -> TrackingUniverseOrigin {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            TrackingUniverseOrigin::from_raw(comp.GetTrackingSpace.unwrap()())
+        }
+    }
+
+    /// Forces interleaved reprojection on, the application only has to render every other frame
+    pub fn force_interleaved_reprojection(&self, on: bool) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.ForceInterleavedReprojectionOn.unwrap()(if on { 1 } else { 0 });
+        }
+    }
+
+    /// Returns true when the application should reduce its rendering quality,
+    ///  for example while the dashboard is shown
+    pub fn should_app_render_with_low_resources(&self) -> bool {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.ShouldAppRenderWithLowResources.unwrap()() > 0
+        }
+    }
+
+    /// Clears the frame that was submitted last, the compositor shows its grid until a new frame arrives
+    ///  use it before pausing the rendering, otherwise the last frame stays visible
+    pub fn clear_last_submitted_frame(&self) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.ClearLastSubmittedFrame.unwrap()();
+        }
+    }
+
+    /// Tells the compositor that the application is done submitting for this frame
+    ///  call it right after the last submit (and after presenting a mirror window), so the compositor
+    ///  can start its work instead of waiting for the next wait_get_poses
+    ///  this only helps within the few milliseconds between the submit and the next vsync
+    pub fn post_present_handoff(&self) {
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            comp.PostPresentHandoff.unwrap()();
+        }
+    }
+
+    /// Blocks until the ideal time to start rendering and returns the poses
+    ///  the first poses are predicted for the time the frame is displayed and should be used for rendering,
+    ///  the second are predicted for the next frame and are meant for game logic
+    ///  has to be called once per frame before submitting
+    pub fn wait_get_poses(&self) -> Result<(TrackedDevicePoses, TrackedDevicePoses), Error<openvr_sys::EVRCompositorError>> {
+        use std;
+
+        unsafe {
+            let comp = * { self.0 as *mut openvr_sys::VR_IVRCompositor_FnTable };
+            let mut render: [openvr_sys::TrackedDevicePose_t; MAX_TRACKED_DEVICE_COUNT as usize] = std::mem::zeroed();
+            let mut game: [openvr_sys::TrackedDevicePose_t; MAX_TRACKED_DEVICE_COUNT as usize] = std::mem::zeroed();
+
+            let err = comp.WaitGetPoses.unwrap()(
+                &mut render[0],
+                MAX_TRACKED_DEVICE_COUNT,
+                &mut game[0],
+                MAX_TRACKED_DEVICE_COUNT
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok((to_tracked(render), to_tracked(game)))
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Renders and submits a single frame
+    ///  waits for the poses, calls render for the left and then the right eye with the render poses
+    ///  submits the returned textures with the given flags and hands the frame off with post_present_handoff
+    ///  stops at the first error of the compositor
+    pub fn frame<F>(&self, flags: SubmitFlags, mut render: F) -> Result<(), Error<openvr_sys::EVRCompositorError>>
+        where F: FnMut(Eye, &TrackedDevicePoses) -> Texture
+    {
+        let (poses, _) = self.wait_get_poses()?;
+
+        for eye in [Eye::Left, Eye::Right].iter() {
+            let texture = render(*eye, &poses);
+            self.submit(*eye, &texture, None, flags)?;
+        }
+
+        self.post_present_handoff();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stub;
+
+    fn compositor(table: &openvr_sys::VR_IVRCompositor_FnTable) -> IVRCompositor {
+        IVRCompositor(stub::table_ptr(table))
+    }
+
+    #[test]
+    fn submit_flags_combine_bits() {
+        let flags = SubmitFlags::LENS_DISTORTION_ALREADY_APPLIED | SubmitFlags::GL_RENDER_BUFFER;
+
+        assert_eq!(flags.bits(), EVRSubmitFlags_Submit_LensDistortionAlreadyApplied as u32 | EVRSubmitFlags_Submit_GlRenderBuffer as u32);
+        assert!(flags.contains(SubmitFlags::GL_RENDER_BUFFER));
+        assert!(flags.contains(SubmitFlags::DEFAULT));
+        assert!(!SubmitFlags::DEFAULT.contains(SubmitFlags::GL_RENDER_BUFFER));
+        assert_eq!(SubmitFlags::default(), SubmitFlags::DEFAULT);
+
+        let mut accumulated = SubmitFlags::DEFAULT;
+        accumulated |= SubmitFlags::GL_RENDER_BUFFER;
+        assert_eq!(accumulated, SubmitFlags::GL_RENDER_BUFFER);
+    }
+
+    #[test]
+    fn set_skybox_rejects_invalid_texture_counts() {
+        // the count is checked before openvr is called, so no runtime is needed
+        let comp = unsafe { IVRCompositor::from_raw(::std::ptr::null()) };
+        let texture = Texture::opengl(1);
+
+        for count in &[0, 3, 4, 5, 7] {
+            let textures = vec![texture; *count];
+            let err = comp.set_skybox(&textures).unwrap_err();
+            assert_eq!(err.to_raw(), EVRCompositorError_VRCompositorError_InvalidTexture);
+        }
+    }
+
+    unsafe extern "C" fn wait_get_poses(render: *mut openvr_sys::TrackedDevicePose_t, render_count: u32, game: *mut openvr_sys::TrackedDevicePose_t, game_count: u32) -> openvr_sys::EVRCompositorError {
+        stub::record("WaitGetPoses");
+        assert_eq!(render_count, MAX_TRACKED_DEVICE_COUNT);
+        assert_eq!(game_count, MAX_TRACKED_DEVICE_COUNT);
+
+        // the hmd is tracked, the game pose is predicted one meter further
+        for &(poses, x) in &[(render, 0.0), (game, 1.0)] {
+            let pose = &mut *poses;
+            pose.bDeviceIsConnected = 1;
+            pose.bPoseIsValid = 1;
+            pose.mDeviceToAbsoluteTracking.m = [[1.0, 0.0, 0.0, x], [0.0, 1.0, 0.0, 1.5], [0.0, 0.0, 1.0, 0.0]];
+            pose.eTrackingResult = openvr_sys::ETrackingResult::ETrackingResult_TrackingResult_Running_OK;
+        }
+        EVRCompositorError_VRCompositorError_None
+    }
+
+    unsafe extern "C" fn wait_get_poses_failing(_: *mut openvr_sys::TrackedDevicePose_t, _: u32, _: *mut openvr_sys::TrackedDevicePose_t, _: u32) -> openvr_sys::EVRCompositorError {
+        stub::record("WaitGetPoses");
+        EVRCompositorError_VRCompositorError_DoNotHaveFocus
+    }
+
+    #[test]
+    fn wait_get_poses_returns_render_and_game_poses() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.WaitGetPoses = stub!(wait_get_poses);
+
+        let (render, game) = compositor(&table).wait_get_poses().unwrap();
+        assert_eq!(stub::take_calls(), vec!["WaitGetPoses"]);
+
+        assert_eq!(render.count, 1);
+        assert_eq!(render.as_slice()[0].position(), [0.0, 1.5, 0.0]);
+        assert_eq!(game.count, 1);
+        assert_eq!(game.as_slice()[0].position(), [1.0, 1.5, 0.0]);
+
+        table.WaitGetPoses = stub!(wait_get_poses_failing);
+        let err = compositor(&table).wait_get_poses().unwrap_err();
+        assert_eq!(err.to_raw(), EVRCompositorError_VRCompositorError_DoNotHaveFocus);
+    }
+
+    // takes the flags as u32 like the transmuted entry in submit
+    unsafe extern "C" fn submit(eye: openvr_sys::EVREye, texture: *mut openvr_sys::Texture_t, bounds: *mut openvr_sys::VRTextureBounds_t, flags: u32) -> openvr_sys::EVRCompositorError {
+        let texture = &*texture;
+        let bounds = if bounds.is_null() {
+            "full".to_string()
+        } else {
+            let b = &*bounds;
+            format!("{} {} {} {}", b.uMin, b.uMax, b.vMin, b.vMax)
+        };
+        stub::record(format!("Submit {} {} {} {}", eye == Eye::Left.to_raw(), texture.handle as usize, bounds, flags));
+
+        if texture.handle.is_null() {
+            EVRCompositorError_VRCompositorError_InvalidTexture
+        } else {
+            EVRCompositorError_VRCompositorError_None
+        }
+    }
+
+    #[test]
+    fn submit_passes_texture_bounds_and_flags() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.Submit = stub!(submit);
+        let comp = compositor(&table);
+        let flags = SubmitFlags::LENS_DISTORTION_ALREADY_APPLIED | SubmitFlags::GL_RENDER_BUFFER;
+
+        comp.submit(Eye::Left, &Texture::opengl(7), None, SubmitFlags::DEFAULT).unwrap();
+        comp.submit(Eye::Right, &Texture::opengl(8), Some(TextureBounds::new((0.5, 1.0), (0.0, 0.25))), flags).unwrap();
+        assert_eq!(stub::take_calls(), vec![
+            format!("Submit true 7 full {}", SubmitFlags::DEFAULT.bits()),
+            format!("Submit false 8 0.5 1 0 0.25 {}", flags.bits()),
+        ]);
+
+        let err = comp.submit(Eye::Left, &Texture::opengl(0), None, SubmitFlags::DEFAULT).unwrap_err();
+        assert_eq!(err.to_raw(), EVRCompositorError_VRCompositorError_InvalidTexture);
+    }
+
+    unsafe extern "C" fn fade_to_color(seconds: f32, r: f32, g: f32, b: f32, a: f32, background: stub::Bool) {
+        stub::record(format!("FadeToColor {} {} {} {} {} {}", seconds, r, g, b, a, background));
+    }
+
+    unsafe extern "C" fn fade_grid(seconds: f32, fade_in: stub::Bool) {
+        stub::record(format!("FadeGrid {} {}", seconds, fade_in));
+    }
+
+    #[test]
+    fn fades_pass_their_arguments() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.FadeToColor = stub!(fade_to_color);
+        table.FadeGrid = stub!(fade_grid);
+        let comp = compositor(&table);
+
+        comp.fade_to_color(0.5, [1.0, 0.25, 0.0, 1.0], true);
+        comp.fade_to_color(2.0, [0.0, 0.0, 0.0, 0.0], false);
+        comp.fade_grid(1.5, true);
+        comp.fade_grid(0.0, false);
+        assert_eq!(stub::take_calls(), vec![
+            "FadeToColor 0.5 1 0.25 0 1 1",
+            "FadeToColor 2 0 0 0 0 0",
+            "FadeGrid 1.5 1",
+            "FadeGrid 0 0",
+        ]);
+    }
+
+    unsafe extern "C" fn get_frame_timing(timing: *mut openvr_sys::Compositor_FrameTiming, frames_ago: u32) -> stub::Bool {
+        let timing = &mut *timing;
+        assert_eq!(timing.m_nSize as usize, ::std::mem::size_of::<openvr_sys::Compositor_FrameTiming>());
+        if frames_ago > 1 {
+            return 0;
+        }
+
+        timing.m_nFrameIndex = 100 - frames_ago;
+        timing.m_nNumFramePresents = 2;
+        timing.m_nNumDroppedFrames = 1;
+        timing.m_flSystemTimeInSeconds = 12.5;
+        timing.m_flSceneRenderGpuMs = 4.0;
+        timing.m_flTotalRenderGpuMs = 5.5;
+        timing.m_flCompositorRenderGpuMs = 1.5;
+        timing.m_flCompositorRenderCpuMs = 0.5;
+        timing.m_flCompositorIdleCpuMs = 6.0;
+        timing.m_flClientFrameIntervalMs = 11.0;
+        timing.m_flPresentCallCpuMs = 0.25;
+        timing.m_flWaitForPresentCpuMs = 0.75;
+        timing.m_flSubmitFrameMs = 0.125;
+        1
+    }
+
+    #[test]
+    fn frame_timing_extracts_the_fields() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.GetFrameTiming = stub!(get_frame_timing);
+        let comp = compositor(&table);
+
+        let timing = comp.frame_timing(1).unwrap();
+        assert_eq!(timing.frame_index, 99);
+        assert_eq!(timing.num_frame_presents, 2);
+        assert_eq!(timing.num_dropped_frames, 1);
+        assert_eq!(timing.system_time_seconds, 12.5);
+        assert_eq!(timing.scene_render_gpu_ms, 4.0);
+        assert_eq!(timing.total_render_gpu_ms, 5.5);
+        assert_eq!(timing.compositor_render_gpu_ms, 1.5);
+        assert_eq!(timing.compositor_render_cpu_ms, 0.5);
+        assert_eq!(timing.compositor_idle_cpu_ms, 6.0);
+        assert_eq!(timing.client_frame_interval_ms, 11.0);
+        assert_eq!(timing.present_call_cpu_ms, 0.25);
+        assert_eq!(timing.wait_for_present_cpu_ms, 0.75);
+        assert_eq!(timing.submit_frame_ms, 0.125);
+
+        assert_eq!(comp.frame_timing(0).unwrap().frame_index, 100);
+        assert!(comp.frame_timing(2).is_none());
+    }
+
+    unsafe extern "C" fn get_cumulative_stats(stats: *mut openvr_sys::Compositor_CumulativeStats, size: u32) {
+        assert_eq!(size as usize, ::std::mem::size_of::<openvr_sys::Compositor_CumulativeStats>());
+        let stats = &mut *stats;
+        stats.m_nPid = 4242;
+        stats.m_nNumFramePresents = 1;
+        stats.m_nNumDroppedFrames = 2;
+        stats.m_nNumReprojectedFrames = 3;
+        stats.m_nNumFramePresentsOnStartup = 4;
+        stats.m_nNumDroppedFramesOnStartup = 5;
+        stats.m_nNumReprojectedFramesOnStartup = 6;
+        stats.m_nNumLoading = 7;
+        stats.m_nNumFramePresentsLoading = 8;
+        stats.m_nNumDroppedFramesLoading = 9;
+        stats.m_nNumReprojectedFramesLoading = 10;
+        stats.m_nNumTimedOut = 11;
+        stats.m_nNumFramePresentsTimedOut = 12;
+        stats.m_nNumDroppedFramesTimedOut = 13;
+        stats.m_nNumReprojectedFramesTimedOut = 14;
+    }
+
+    #[test]
+    fn cumulative_stats_extracts_the_fields() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.GetCumulativeStats = stub!(get_cumulative_stats);
+
+        let stats = compositor(&table).cumulative_stats();
+        assert_eq!(stats.pid, 4242);
+        assert_eq!(
+            [stats.num_frame_presents, stats.num_dropped_frames, stats.num_reprojected_frames],
+            [1, 2, 3]
+        );
+        assert_eq!(
+            [stats.num_frame_presents_on_startup, stats.num_dropped_frames_on_startup, stats.num_reprojected_frames_on_startup],
+            [4, 5, 6]
+        );
+        assert_eq!(
+            [stats.num_loading, stats.num_frame_presents_loading, stats.num_dropped_frames_loading, stats.num_reprojected_frames_loading],
+            [7, 8, 9, 10]
+        );
+        assert_eq!(
+            [stats.num_timed_out, stats.num_frame_presents_timed_out, stats.num_dropped_frames_timed_out, stats.num_reprojected_frames_timed_out],
+            [11, 12, 13, 14]
+        );
+    }
+
+    thread_local! {
+        static TRACKING_SPACE: ::std::cell::Cell<openvr_sys::ETrackingUniverseOrigin> =
+            ::std::cell::Cell::new(TrackingUniverseOrigin::Standing.to_raw());
+    }
+
+    unsafe extern "C" fn set_tracking_space(origin: openvr_sys::ETrackingUniverseOrigin) {
+        stub::record(format!("SetTrackingSpace {:?}", TrackingUniverseOrigin::from_raw(origin)));
+        TRACKING_SPACE.with(|space| space.set(origin));
+    }
+
+    unsafe extern "C" fn get_tracking_space() -> openvr_sys::ETrackingUniverseOrigin {
+        TRACKING_SPACE.with(|space| space.get())
+    }
+
+    #[test]
+    fn tracking_space_round_trips() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.SetTrackingSpace = stub!(set_tracking_space);
+        table.GetTrackingSpace = stub!(get_tracking_space);
+        let comp = compositor(&table);
+
+        for origin in &[TrackingUniverseOrigin::Seated, TrackingUniverseOrigin::RawAndUncalibrated, TrackingUniverseOrigin::Standing] {
+            comp.set_tracking_space(*origin);
+            assert_eq!(comp.tracking_space(), *origin);
+        }
+        assert_eq!(stub::take_calls(), vec![
+            "SetTrackingSpace Seated",
+            "SetTrackingSpace RawAndUncalibrated",
+            "SetTrackingSpace Standing",
+        ]);
+    }
+
+    unsafe extern "C" fn force_interleaved_reprojection_on(on: stub::Bool) {
+        stub::record(format!("ForceInterleavedReprojectionOn {}", on));
+    }
+
+    unsafe extern "C" fn should_app_render_with_low_resources() -> stub::Bool {
+        1
+    }
+
+    unsafe extern "C" fn should_app_render_with_full_resources() -> stub::Bool {
+        0
+    }
+
+    #[test]
+    fn reprojection_and_low_resources_pass_through() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.ForceInterleavedReprojectionOn = stub!(force_interleaved_reprojection_on);
+        table.ShouldAppRenderWithLowResources = stub!(should_app_render_with_low_resources);
+
+        compositor(&table).force_interleaved_reprojection(true);
+        compositor(&table).force_interleaved_reprojection(false);
+        assert_eq!(stub::take_calls(), vec!["ForceInterleavedReprojectionOn 1", "ForceInterleavedReprojectionOn 0"]);
+
+        assert!(compositor(&table).should_app_render_with_low_resources());
+        table.ShouldAppRenderWithLowResources = stub!(should_app_render_with_full_resources);
+        assert!(!compositor(&table).should_app_render_with_low_resources());
+    }
+
+    unsafe extern "C" fn clear_last_submitted_frame() {
+        stub::record("ClearLastSubmittedFrame");
+    }
+
+    unsafe extern "C" fn post_present_handoff() {
+        stub::record("PostPresentHandoff");
+    }
+
+    #[test]
+    fn clear_and_handoff_fire_their_entries() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.ClearLastSubmittedFrame = stub!(clear_last_submitted_frame);
+        table.PostPresentHandoff = stub!(post_present_handoff);
+        let comp = compositor(&table);
+
+        comp.clear_last_submitted_frame();
+        assert_eq!(stub::take_calls(), vec!["ClearLastSubmittedFrame"]);
+        comp.post_present_handoff();
+        assert_eq!(stub::take_calls(), vec!["PostPresentHandoff"]);
+    }
+
+    #[test]
+    fn frame_waits_renders_submits_and_hands_off_in_order() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRCompositor_FnTable>();
+        table.WaitGetPoses = stub!(wait_get_poses);
+        table.Submit = stub!(submit);
+        table.PostPresentHandoff = stub!(post_present_handoff);
+        let comp = compositor(&table);
+
+        comp.frame(SubmitFlags::DEFAULT, |eye, poses| {
+            stub::record(format!("render {:?} {}", eye, poses.count));
+            Texture::opengl(if eye == Eye::Left { 1 } else { 2 })
+        }).unwrap();
+
+        let flags = SubmitFlags::DEFAULT.bits();
+        assert_eq!(stub::take_calls(), vec![
+            "WaitGetPoses".to_string(),
+            "render Left 1".to_string(),
+            format!("Submit true 1 full {}", flags),
+            "render Right 1".to_string(),
+            format!("Submit false 2 full {}", flags),
+            "PostPresentHandoff".to_string(),
+        ]);
+
+        // nothing is rendered or submitted without poses
+        table.WaitGetPoses = stub!(wait_get_poses_failing);
+        let err = compositor(&table).frame(SubmitFlags::DEFAULT, |_, _| Texture::opengl(1)).unwrap_err();
+        assert_eq!(err.to_raw(), EVRCompositorError_VRCompositorError_DoNotHaveFocus);
+        assert_eq!(stub::take_calls(), vec!["WaitGetPoses"]);
+    }
 }
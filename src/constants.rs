@@ -0,0 +1,47 @@
+//! Constants of the openvr api
+//!  re-exported from openvr_sys, so user code does not have to depend on it
+
+use openvr_sys;
+
+/// Maximum number of tracked devices, device indices are always smaller
+pub const MAX_TRACKED_DEVICE_COUNT: u32 = openvr_sys::k_unMaxTrackedDeviceCount;
+
+/// The index of the hmd, it is always the first device
+pub const TRACKED_DEVICE_INDEX_HMD: u32 = openvr_sys::k_unTrackedDeviceIndex_Hmd;
+
+/// Index returned when no device was found
+pub const TRACKED_DEVICE_INDEX_INVALID: u32 = openvr_sys::k_unTrackedDeviceIndexInvalid;
+
+/// Maximum size of a string property, including the terminator
+pub const MAX_PROPERTY_STRING_SIZE: u32 = openvr_sys::k_unMaxPropertyStringSize;
+
+/// Number of axes reported in a controller state
+pub const CONTROLLER_STATE_AXIS_COUNT: u32 = openvr_sys::k_unControllerStateAxisCount;
+
+/// Handle value that never refers to an overlay
+pub const OVERLAY_HANDLE_INVALID: u64 = openvr_sys::k_ulOverlayHandleInvalid as u64;
+
+/// Maximum length of an application key, including the terminator
+pub const MAX_APPLICATION_KEY_LENGTH: u32 = openvr_sys::k_unMaxApplicationKeyLength;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_match_the_sdk() {
+        assert_eq!(MAX_TRACKED_DEVICE_COUNT, 16);
+        assert_eq!(TRACKED_DEVICE_INDEX_HMD, 0);
+        assert_eq!(TRACKED_DEVICE_INDEX_INVALID, 0xFFFFFFFF);
+        assert_eq!(MAX_PROPERTY_STRING_SIZE, 32 * 1024);
+        assert_eq!(CONTROLLER_STATE_AXIS_COUNT, 5);
+        assert_eq!(OVERLAY_HANDLE_INVALID, 0);
+        assert_eq!(MAX_APPLICATION_KEY_LENGTH, 128);
+    }
+
+    #[test]
+    fn controller_state_has_one_entry_per_sys_axis() {
+        let raw: openvr_sys::VRControllerState_t = unsafe { ::std::mem::zeroed() };
+        assert_eq!(raw.rAxis.len(), CONTROLLER_STATE_AXIS_COUNT as usize);
+    }
+}
@@ -0,0 +1,237 @@
+use openvr_sys;
+use openvr_sys::EVRInitError::*;
+
+use std::error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::*;
+use error::*;
+use subsystems;
+use subsystems::Subsystem;
+use system::IVRSystem;
+use extended_display::IVRExtendedDisplay;
+use compositor::IVRCompositor;
+use render_models::IVRRenderModels;
+use tracked_camera::IVRTrackedCamera;
+use chaperone::IVRChaperone;
+use chaperone_setup::IVRChaperoneSetup;
+use overlay::IVROverlay;
+use applications::IVRApplications;
+use settings::IVRSettings;
+
+// The owner of the initialized runtime, NO_OWNER while openvr is not initialized
+static OWNER: AtomicUsize = AtomicUsize::new(NO_OWNER);
+const NO_OWNER: usize = 0;
+
+/// Who initialized the runtime, only the owner can shut it down again
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Owner {
+    /// the free openvr::init and openvr::shutdown functions
+    Free = 1,
+    /// a Context, the runtime lives until the context is dropped
+    Context = 2
+}
+
+/// Error returned when the openvr runtime could not be initialized
+#[derive(Debug)]
+pub enum ContextError {
+    /// openvr is already initialized by another Context or by openvr::init
+    AlreadyInitialized,
+    /// the runtime reported an error
+    Init(Error<openvr_sys::EVRInitError>)
+}
+
+impl From<Error<openvr_sys::EVRInitError>> for ContextError {
+    fn from(err: Error<openvr_sys::EVRInitError>) -> ContextError {
+        ContextError::Init(err)
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ContextError::AlreadyInitialized => write!(f, "openvr is already initialized"),
+            ContextError::Init(ref err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl error::Error for ContextError {}
+
+#[cfg(not(test))]
+fn init_internal(application_type: ApplicationType) -> openvr_sys::EVRInitError {
+    let mut err = EVRInitError_VRInitError_None;
+
+    unsafe {
+        openvr_sys::VR_InitInternal(&mut err, application_type.to_raw());
+    }
+
+    err
+}
+
+#[cfg(not(test))]
+fn shutdown_internal() {
+    unsafe {
+        openvr_sys::VR_ShutdownInternal();
+    }
+}
+
+/// Inits the runtime for the given owner
+///  a repeated free init keeps the running runtime, any other second init fails with AlreadyInitialized
+pub(crate) fn init_runtime(application_type: ApplicationType, owner: Owner) -> Result<(), ContextError> {
+    match OWNER.compare_exchange(NO_OWNER, owner as usize, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(_) => {},
+        Err(current) if current == Owner::Free as usize && owner == Owner::Free => return Ok(()),
+        Err(_) => return Err(ContextError::AlreadyInitialized)
+    }
+
+    match init_internal(application_type) {
+        EVRInitError_VRInitError_None => Ok(()),
+        err => {
+            OWNER.store(NO_OWNER, Ordering::SeqCst);
+            Err(ContextError::Init(Error::from_raw(err)))
+        }
+    }
+}
+
+/// Shuts the runtime down if the given owner initialized it, otherwise nothing happens
+pub(crate) fn shutdown_runtime(owner: Owner) {
+    if OWNER.compare_exchange(owner as usize, NO_OWNER, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+        shutdown_internal();
+    }
+}
+
+/// An initialized openvr runtime, openvr is shut down when the context is dropped
+///  the subsystems borrow the context, so they can not be used after it is gone
+///  only one context can exist at a time, openvr::shutdown does not affect it
+pub struct Context {
+    _private: ()
+}
+
+impl Context {
+    /// Inits the openvr runtime for the given application type
+    ///  fails with AlreadyInitialized while another context (or openvr::init) holds the runtime
+    pub fn init(application_type: ApplicationType) -> Result<Context, ContextError> {
+        init_runtime(application_type, Owner::Context)?;
+        Ok(Context { _private: () })
+    }
+
+    /// gets the vr system interface
+    pub fn system(&self) -> Result<Subsystem<IVRSystem>, Error<openvr_sys::EVRInitError>> {
+        subsystems::system().map(Subsystem::new)
+    }
+
+    /// gets the vr extended display interface
+    pub fn extended_display(&self) -> Result<Subsystem<IVRExtendedDisplay>, Error<openvr_sys::EVRInitError>> {
+        subsystems::extended_display().map(Subsystem::new)
+    }
+
+    /// gets the vr compositor interface
+    pub fn compositor(&self) -> Result<Subsystem<IVRCompositor>, Error<openvr_sys::EVRInitError>> {
+        subsystems::compositor().map(Subsystem::new)
+    }
+
+    /// gets the vr render models interface
+    pub fn render_models(&self) -> Result<Subsystem<IVRRenderModels>, Error<openvr_sys::EVRInitError>> {
+        subsystems::render_models().map(Subsystem::new)
+    }
+
+    /// gets the vr tracked camera interface
+    pub fn tracked_camera(&self) -> Result<Subsystem<IVRTrackedCamera>, Error<openvr_sys::EVRInitError>> {
+        subsystems::tracked_camera().map(Subsystem::new)
+    }
+
+    /// gets the vr chaperone interface
+    pub fn chaperone(&self) -> Result<Subsystem<IVRChaperone>, Error<openvr_sys::EVRInitError>> {
+        subsystems::chaperone().map(Subsystem::new)
+    }
+
+    /// gets the vr chaperone setup interface
+    pub fn chaperone_setup(&self) -> Result<Subsystem<IVRChaperoneSetup>, Error<openvr_sys::EVRInitError>> {
+        subsystems::chaperone_setup().map(Subsystem::new)
+    }
+
+    /// gets the vr overlay interface
+    pub fn overlay(&self) -> Result<Subsystem<IVROverlay>, Error<openvr_sys::EVRInitError>> {
+        subsystems::overlay().map(Subsystem::new)
+    }
+
+    /// gets the vr applications interface
+    pub fn applications(&self) -> Result<Subsystem<IVRApplications>, Error<openvr_sys::EVRInitError>> {
+        subsystems::applications().map(Subsystem::new)
+    }
+
+    /// gets the vr settings interface
+    pub fn settings(&self) -> Result<Subsystem<IVRSettings>, Error<openvr_sys::EVRInitError>> {
+        subsystems::settings().map(Subsystem::new)
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        shutdown_runtime(Owner::Context);
+    }
+}
+
+// Tests count the runtime calls instead of talking to a real runtime
+#[cfg(test)]
+static SHUTDOWN_CALLS: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
+
+#[cfg(test)]
+fn init_internal(_: ApplicationType) -> openvr_sys::EVRInitError {
+    EVRInitError_VRInitError_None
+}
+
+#[cfg(test)]
+fn shutdown_internal() {
+    SHUTDOWN_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shutdown_calls() -> usize {
+        SHUTDOWN_CALLS.load(Ordering::SeqCst)
+    }
+
+    // the runtime is global, so all steps share one test to run in order
+    #[test]
+    fn only_the_owner_shuts_the_runtime_down() {
+        let context = Context::init(ApplicationType::Scene).unwrap();
+        match Context::init(ApplicationType::Scene) {
+            Err(ContextError::AlreadyInitialized) => {},
+            _ => panic!("second context was created")
+        }
+        match init_runtime(ApplicationType::Scene, Owner::Free) {
+            Err(ContextError::AlreadyInitialized) => {},
+            _ => panic!("free init succeeded while a context exists")
+        }
+
+        // the free shutdown must not kill the runtime of a living context
+        shutdown_runtime(Owner::Free);
+        assert_eq!(shutdown_calls(), 0);
+
+        drop(context);
+        assert_eq!(shutdown_calls(), 1);
+
+        // the free shutdown after the context is gone must not shut down again
+        shutdown_runtime(Owner::Free);
+        assert_eq!(shutdown_calls(), 1);
+
+        // repeated free inits share one runtime, which a context can not take over
+        init_runtime(ApplicationType::Scene, Owner::Free).unwrap();
+        init_runtime(ApplicationType::Scene, Owner::Free).unwrap();
+        match Context::init(ApplicationType::Scene) {
+            Err(ContextError::AlreadyInitialized) => {},
+            _ => panic!("context was created while the free runtime is initialized")
+        }
+        shutdown_runtime(Owner::Free);
+        assert_eq!(shutdown_calls(), 2);
+
+        let context = Context::init(ApplicationType::Scene).unwrap();
+        drop(context);
+        assert_eq!(shutdown_calls(), 3);
+    }
+}
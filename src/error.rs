@@ -1,11 +1,18 @@
 use openvr_sys;
 use subsystems::*;
+use std::error;
+use std::fmt;
 
 pub trait RawError {
     fn is_err(&self) -> bool;
     fn message(&self) -> String;
 }
 
+/// Gives an english description of a raw error, without requiring the runtime
+pub trait DescribeError {
+    fn describe(&self) -> &'static str;
+}
+
 #[derive(Debug)]
 pub struct Error<Err: RawError + Copy> {
     raw: Err
@@ -40,6 +47,28 @@ impl<Err: RawError + Copy> Error<Err> {
     }
 }
 
+impl<Err: RawError + DescribeError + Copy> Error<Err> {
+    /// Gets an english description of the error (does not require the runtime)
+    pub fn describe(&self) -> &'static str {
+        self.raw.describe()
+    }
+}
+
+impl<Err: RawError + DescribeError + Copy> fmt::Display for Error<Err> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+impl<Err: RawError + DescribeError + Copy + fmt::Debug> error::Error for Error<Err> {}
+
+/// Implemented by errors of asynchronous openvr calls
+///  is_loading returning true means the call has to be repeated later, the error is no real failure
+pub trait AsyncError {
+    /// checks if result is currently loading
+    fn is_loading(&self) -> bool;
+}
+
 // OpenVR implement per error type a new function to get a error string
 //  for easier use, this macro will generate easily the RawError trait
 macro_rules! impl_raw_error {
@@ -74,6 +103,10 @@ use openvr_sys::ETrackedPropertyError::*;
 use openvr_sys::EVRInitError::*;
 use openvr_sys::EVRRenderModelError::*;
 use openvr_sys::EVRTrackedCameraError::*;
+use openvr_sys::EVRCompositorError::*;
+use openvr_sys::EVROverlayError::*;
+use openvr_sys::EVRApplicationError::*;
+use openvr_sys::EVRSettingsError::*;
 
 impl_raw_error!(
     system,
@@ -96,15 +129,36 @@ impl_raw_error!(
     EVRTrackedCameraError,
     EVRTrackedCameraError_VRTrackedCameraError_None);
 
+impl_raw_error!(
+    overlay,
+    VR_IVROverlay_FnTable,
+    GetOverlayErrorNameFromEnum,
+    EVROverlayError,
+    EVROverlayError_VROverlayError_None);
+
+impl_raw_error!(
+    applications,
+    VR_IVRApplications_FnTable,
+    GetApplicationsErrorNameFromEnum,
+    EVRApplicationError,
+    EVRApplicationError_VRApplicationError_None);
+
+impl_raw_error!(
+    settings,
+    VR_IVRSettings_FnTable,
+    GetSettingsErrorNameFromEnum,
+    EVRSettingsError,
+    EVRSettingsError_VRSettingsError_None);
+
 // The init error has some special function to retrieve string
 impl RawError for EVRInitError {
     fn is_err(&self) -> bool {
         match *self {
             EVRInitError_VRInitError_None => {
-                true
+                false
             },
             _ => {
-                false
+                true
             }
         }
     }
@@ -117,3 +171,431 @@ impl RawError for EVRInitError {
         String::from(sstr)
     }
 }
+
+// The compositor offers no function to retrieve the error string, use the english description instead
+impl RawError for EVRCompositorError {
+    fn is_err(&self) -> bool {
+        match *self {
+            EVRCompositorError_VRCompositorError_None => {
+                false
+            },
+            _ => {
+                true
+            }
+        }
+    }
+
+    fn message(&self) -> String {
+        String::from(self.describe())
+    }
+}
+
+impl DescribeError for EVRCompositorError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVRCompositorError_VRCompositorError_None => "no error",
+            EVRCompositorError_VRCompositorError_RequestFailed => "compositor request failed",
+            EVRCompositorError_VRCompositorError_IncompatibleVersion => "incompatible compositor version",
+            EVRCompositorError_VRCompositorError_DoNotHaveFocus => "application does not have focus",
+            EVRCompositorError_VRCompositorError_InvalidTexture => "invalid texture",
+            EVRCompositorError_VRCompositorError_IsNotSceneApplication => "application is not a scene application",
+            EVRCompositorError_VRCompositorError_TextureIsOnWrongDevice => "texture is on the wrong device",
+            EVRCompositorError_VRCompositorError_TextureUsesUnsupportedFormat => "texture uses an unsupported format",
+            EVRCompositorError_VRCompositorError_SharedTexturesNotSupported => "shared textures are not supported",
+            EVRCompositorError_VRCompositorError_IndexOutOfRange => "index out of range"
+        }
+    }
+}
+
+impl DescribeError for EVRRenderModelError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVRRenderModelError_VRRenderModelError_None => "no error",
+            EVRRenderModelError_VRRenderModelError_Loading => "render model is still loading",
+            EVRRenderModelError_VRRenderModelError_NotSupported => "render models are not supported",
+            EVRRenderModelError_VRRenderModelError_InvalidArg => "invalid argument",
+            EVRRenderModelError_VRRenderModelError_InvalidModel => "invalid render model",
+            EVRRenderModelError_VRRenderModelError_NoShapes => "render model has no shapes",
+            EVRRenderModelError_VRRenderModelError_MultipleShapes => "render model has multiple shapes",
+            EVRRenderModelError_VRRenderModelError_TooManyVertices => "render model has too many vertices",
+            EVRRenderModelError_VRRenderModelError_MultipleTextures => "render model has multiple textures",
+            EVRRenderModelError_VRRenderModelError_BufferTooSmall => "buffer too small",
+            EVRRenderModelError_VRRenderModelError_NotEnoughNormals => "render model has not enough normals",
+            EVRRenderModelError_VRRenderModelError_NotEnoughTexCoords => "render model has not enough texture coordinates",
+            EVRRenderModelError_VRRenderModelError_InvalidTexture => "invalid texture"
+        }
+    }
+}
+
+impl DescribeError for ETrackedPropertyError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            ETrackedPropertyError_TrackedProp_Success => "no error",
+            ETrackedPropertyError_TrackedProp_WrongDataType => "property has a different data type",
+            ETrackedPropertyError_TrackedProp_WrongDeviceClass => "property is not available for this device class",
+            ETrackedPropertyError_TrackedProp_BufferTooSmall => "buffer too small",
+            ETrackedPropertyError_TrackedProp_UnknownProperty => "unknown property",
+            ETrackedPropertyError_TrackedProp_InvalidDevice => "invalid device index",
+            ETrackedPropertyError_TrackedProp_CouldNotContactServer => "could not contact the vr server",
+            ETrackedPropertyError_TrackedProp_ValueNotProvidedByDevice => "property is not provided by the device",
+            ETrackedPropertyError_TrackedProp_StringExceedsMaximumLength => "string exceeds the maximum length",
+            ETrackedPropertyError_TrackedProp_NotYetAvailable => "property is not available yet"
+        }
+    }
+}
+
+// Only the init errors of the bound sdk version are named, newer runtimes report more of them
+impl DescribeError for EVRInitError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVRInitError_VRInitError_None => "no error",
+            EVRInitError_VRInitError_Unknown => "unknown initialization error",
+            EVRInitError_VRInitError_Init_InstallationNotFound => "openvr installation not found",
+            EVRInitError_VRInitError_Init_InstallationCorrupt => "openvr installation is corrupt",
+            EVRInitError_VRInitError_Init_VRClientDLLNotFound => "vr client library not found",
+            EVRInitError_VRInitError_Init_FileNotFound => "file not found",
+            EVRInitError_VRInitError_Init_FactoryNotFound => "interface factory not found",
+            EVRInitError_VRInitError_Init_InterfaceNotFound => "interface not found",
+            EVRInitError_VRInitError_Init_InvalidInterface => "invalid interface",
+            EVRInitError_VRInitError_Init_UserConfigDirectoryInvalid => "user config directory is invalid",
+            EVRInitError_VRInitError_Init_HmdNotFound => "hmd not found",
+            EVRInitError_VRInitError_Init_NotInitialized => "openvr is not initialized",
+            EVRInitError_VRInitError_Init_PathRegistryNotFound => "path registry not found",
+            EVRInitError_VRInitError_Init_NoConfigPath => "no config path",
+            EVRInitError_VRInitError_Init_NoLogPath => "no log path",
+            EVRInitError_VRInitError_Init_PathRegistryNotWritable => "path registry is not writable",
+            EVRInitError_VRInitError_Init_AppInfoInitFailed => "application info initialization failed",
+            EVRInitError_VRInitError_Init_Retry => "initialization has to be retried",
+            EVRInitError_VRInitError_Init_InitCanceledByUser => "initialization was canceled by the user",
+            EVRInitError_VRInitError_Init_AnotherAppLaunching => "another application is launching",
+            EVRInitError_VRInitError_Init_SettingsInitFailed => "settings initialization failed",
+            EVRInitError_VRInitError_Init_ShuttingDown => "openvr is shutting down",
+            EVRInitError_VRInitError_Init_TooManyObjects => "too many objects",
+            EVRInitError_VRInitError_Init_NoServerForBackgroundApp => "no vr server running for the background application",
+            EVRInitError_VRInitError_Init_NotSupportedWithCompositor => "not supported with the compositor",
+            EVRInitError_VRInitError_Init_NotAvailableToUtilityApps => "not available to utility applications",
+            EVRInitError_VRInitError_Driver_Failed => "driver failed",
+            EVRInitError_VRInitError_Driver_Unknown => "unknown driver",
+            EVRInitError_VRInitError_Driver_HmdUnknown => "unknown hmd",
+            EVRInitError_VRInitError_Driver_NotLoaded => "driver is not loaded",
+            EVRInitError_VRInitError_Driver_RuntimeOutOfDate => "runtime is out of date",
+            EVRInitError_VRInitError_Driver_HmdInUse => "hmd is in use by another application",
+            EVRInitError_VRInitError_Driver_NotCalibrated => "hmd is not calibrated",
+            EVRInitError_VRInitError_Driver_CalibrationInvalid => "hmd calibration is invalid",
+            EVRInitError_VRInitError_Driver_HmdDisplayNotFound => "hmd display not found",
+            EVRInitError_VRInitError_IPC_ServerInitFailed => "vr server initialization failed",
+            EVRInitError_VRInitError_IPC_ConnectFailed => "could not connect to the vr server",
+            EVRInitError_VRInitError_IPC_SharedStateInitFailed => "shared state initialization failed",
+            EVRInitError_VRInitError_IPC_CompositorInitFailed => "compositor initialization failed",
+            EVRInitError_VRInitError_IPC_MutexInitFailed => "mutex initialization failed",
+            EVRInitError_VRInitError_IPC_Failed => "vr server communication failed",
+            EVRInitError_VRInitError_Compositor_Failed => "compositor failed",
+            EVRInitError_VRInitError_Compositor_D3D11HardwareRequired => "compositor requires d3d11 hardware",
+            EVRInitError_VRInitError_Steam_SteamInstallationNotFound => "steam installation not found",
+            _ => "openvr initialization failed"
+        }
+    }
+}
+
+impl DescribeError for EVROverlayError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVROverlayError_VROverlayError_None => "no error",
+            EVROverlayError_VROverlayError_UnknownOverlay => "unknown overlay",
+            EVROverlayError_VROverlayError_InvalidHandle => "invalid overlay handle",
+            EVROverlayError_VROverlayError_PermissionDenied => "permission denied",
+            EVROverlayError_VROverlayError_OverlayLimitExceeded => "overlay limit exceeded",
+            EVROverlayError_VROverlayError_WrongVisibilityType => "wrong visibility type",
+            EVROverlayError_VROverlayError_KeyTooLong => "overlay key too long",
+            EVROverlayError_VROverlayError_NameTooLong => "overlay name too long",
+            EVROverlayError_VROverlayError_KeyInUse => "overlay key already in use",
+            EVROverlayError_VROverlayError_WrongTransformType => "wrong transform type",
+            EVROverlayError_VROverlayError_InvalidTrackedDevice => "invalid tracked device",
+            EVROverlayError_VROverlayError_InvalidParameter => "invalid parameter",
+            EVROverlayError_VROverlayError_ThumbnailCantBeDestroyed => "thumbnail can not be destroyed",
+            EVROverlayError_VROverlayError_ArrayTooSmall => "array too small",
+            EVROverlayError_VROverlayError_RequestFailed => "overlay request failed",
+            EVROverlayError_VROverlayError_InvalidTexture => "invalid texture",
+            EVROverlayError_VROverlayError_UnableToLoadFile => "unable to load file",
+            EVROverlayError_VROverlayError_KeyboardAlreadyInUse => "keyboard already in use",
+            EVROverlayError_VROverlayError_NoNeighbor => "no neighbor overlay",
+            _ => "overlay error"
+        }
+    }
+}
+
+impl DescribeError for EVRApplicationError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVRApplicationError_VRApplicationError_None => "no error",
+            EVRApplicationError_VRApplicationError_AppKeyAlreadyExists => "application key already exists",
+            EVRApplicationError_VRApplicationError_NoManifest => "no manifest",
+            EVRApplicationError_VRApplicationError_NoApplication => "no application",
+            EVRApplicationError_VRApplicationError_InvalidIndex => "invalid application index",
+            EVRApplicationError_VRApplicationError_UnknownApplication => "unknown application",
+            EVRApplicationError_VRApplicationError_IPCFailed => "vr server communication failed",
+            EVRApplicationError_VRApplicationError_ApplicationAlreadyRunning => "application is already running",
+            EVRApplicationError_VRApplicationError_InvalidManifest => "invalid manifest",
+            EVRApplicationError_VRApplicationError_InvalidApplication => "invalid application",
+            EVRApplicationError_VRApplicationError_LaunchFailed => "application launch failed",
+            EVRApplicationError_VRApplicationError_ApplicationAlreadyStarting => "application is already starting",
+            EVRApplicationError_VRApplicationError_LaunchInProgress => "application launch in progress",
+            EVRApplicationError_VRApplicationError_OldApplicationQuitting => "old application is quitting",
+            EVRApplicationError_VRApplicationError_TransitionAborted => "transition aborted",
+            EVRApplicationError_VRApplicationError_IsTemplate => "application is a template",
+            EVRApplicationError_VRApplicationError_BufferTooSmall => "buffer too small",
+            EVRApplicationError_VRApplicationError_PropertyNotSet => "property not set",
+            EVRApplicationError_VRApplicationError_UnknownProperty => "unknown property",
+            _ => "application error"
+        }
+    }
+}
+
+impl DescribeError for EVRSettingsError {
+    fn describe(&self) -> &'static str {
+        match *self {
+            EVRSettingsError_VRSettingsError_None => "no error",
+            EVRSettingsError_VRSettingsError_IPCFailed => "vr server communication failed",
+            EVRSettingsError_VRSettingsError_WriteFailed => "writing the settings failed",
+            EVRSettingsError_VRSettingsError_ReadFailed => "reading the settings failed",
+            _ => "settings error"
+        }
+    }
+}
+
+impl AsyncError for Error<EVRRenderModelError> {
+    fn is_loading(&self) -> bool {
+        match self.raw {
+            EVRRenderModelError_VRRenderModelError_Loading => {
+                true
+            },
+            _ => {
+                false
+            }
+        }
+    }
+}
+
+impl Error<EVRRenderModelError> {
+    /// the render model name is unknown, trying a fallback model might help
+    pub fn is_invalid_model(&self) -> bool {
+        match self.raw {
+            EVRRenderModelError_VRRenderModelError_InvalidModel => true,
+            _ => false
+        }
+    }
+
+    /// render models are not supported by the current runtime
+    pub fn is_not_supported(&self) -> bool {
+        match self.raw {
+            EVRRenderModelError_VRRenderModelError_NotSupported => true,
+            _ => false
+        }
+    }
+
+    /// the render model has no shapes to render
+    pub fn is_no_shapes(&self) -> bool {
+        match self.raw {
+            EVRRenderModelError_VRRenderModelError_NoShapes => true,
+            _ => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // every named raw error needs its own non empty description
+    fn assert_described<E: DescribeError>(table: &[E]) {
+        let mut seen = HashSet::new();
+        for raw in table {
+            let description = raw.describe();
+            assert!(!description.is_empty());
+            assert!(seen.insert(description), "duplicate description {:?}", description);
+        }
+    }
+
+    #[test]
+    fn describes_init_errors() {
+        assert_described(&[
+            EVRInitError_VRInitError_None,
+            EVRInitError_VRInitError_Unknown,
+            EVRInitError_VRInitError_Init_InstallationNotFound,
+            EVRInitError_VRInitError_Init_InstallationCorrupt,
+            EVRInitError_VRInitError_Init_VRClientDLLNotFound,
+            EVRInitError_VRInitError_Init_FileNotFound,
+            EVRInitError_VRInitError_Init_FactoryNotFound,
+            EVRInitError_VRInitError_Init_InterfaceNotFound,
+            EVRInitError_VRInitError_Init_InvalidInterface,
+            EVRInitError_VRInitError_Init_UserConfigDirectoryInvalid,
+            EVRInitError_VRInitError_Init_HmdNotFound,
+            EVRInitError_VRInitError_Init_NotInitialized,
+            EVRInitError_VRInitError_Init_PathRegistryNotFound,
+            EVRInitError_VRInitError_Init_NoConfigPath,
+            EVRInitError_VRInitError_Init_NoLogPath,
+            EVRInitError_VRInitError_Init_PathRegistryNotWritable,
+            EVRInitError_VRInitError_Init_AppInfoInitFailed,
+            EVRInitError_VRInitError_Init_Retry,
+            EVRInitError_VRInitError_Init_InitCanceledByUser,
+            EVRInitError_VRInitError_Init_AnotherAppLaunching,
+            EVRInitError_VRInitError_Init_SettingsInitFailed,
+            EVRInitError_VRInitError_Init_ShuttingDown,
+            EVRInitError_VRInitError_Init_TooManyObjects,
+            EVRInitError_VRInitError_Init_NoServerForBackgroundApp,
+            EVRInitError_VRInitError_Init_NotSupportedWithCompositor,
+            EVRInitError_VRInitError_Init_NotAvailableToUtilityApps,
+            EVRInitError_VRInitError_Driver_Failed,
+            EVRInitError_VRInitError_Driver_Unknown,
+            EVRInitError_VRInitError_Driver_HmdUnknown,
+            EVRInitError_VRInitError_Driver_NotLoaded,
+            EVRInitError_VRInitError_Driver_RuntimeOutOfDate,
+            EVRInitError_VRInitError_Driver_HmdInUse,
+            EVRInitError_VRInitError_Driver_NotCalibrated,
+            EVRInitError_VRInitError_Driver_CalibrationInvalid,
+            EVRInitError_VRInitError_Driver_HmdDisplayNotFound,
+            EVRInitError_VRInitError_IPC_ServerInitFailed,
+            EVRInitError_VRInitError_IPC_ConnectFailed,
+            EVRInitError_VRInitError_IPC_SharedStateInitFailed,
+            EVRInitError_VRInitError_IPC_CompositorInitFailed,
+            EVRInitError_VRInitError_IPC_MutexInitFailed,
+            EVRInitError_VRInitError_IPC_Failed,
+            EVRInitError_VRInitError_Compositor_Failed,
+            EVRInitError_VRInitError_Compositor_D3D11HardwareRequired,
+            EVRInitError_VRInitError_Steam_SteamInstallationNotFound]);
+    }
+
+    #[test]
+    fn describes_compositor_errors() {
+        assert_described(&[
+            EVRCompositorError_VRCompositorError_None,
+            EVRCompositorError_VRCompositorError_RequestFailed,
+            EVRCompositorError_VRCompositorError_IncompatibleVersion,
+            EVRCompositorError_VRCompositorError_DoNotHaveFocus,
+            EVRCompositorError_VRCompositorError_InvalidTexture,
+            EVRCompositorError_VRCompositorError_IsNotSceneApplication,
+            EVRCompositorError_VRCompositorError_TextureIsOnWrongDevice,
+            EVRCompositorError_VRCompositorError_TextureUsesUnsupportedFormat,
+            EVRCompositorError_VRCompositorError_SharedTexturesNotSupported,
+            EVRCompositorError_VRCompositorError_IndexOutOfRange]);
+    }
+
+    #[test]
+    fn describes_render_model_errors() {
+        assert_described(&[
+            EVRRenderModelError_VRRenderModelError_None,
+            EVRRenderModelError_VRRenderModelError_Loading,
+            EVRRenderModelError_VRRenderModelError_NotSupported,
+            EVRRenderModelError_VRRenderModelError_InvalidArg,
+            EVRRenderModelError_VRRenderModelError_InvalidModel,
+            EVRRenderModelError_VRRenderModelError_NoShapes,
+            EVRRenderModelError_VRRenderModelError_MultipleShapes,
+            EVRRenderModelError_VRRenderModelError_TooManyVertices,
+            EVRRenderModelError_VRRenderModelError_MultipleTextures,
+            EVRRenderModelError_VRRenderModelError_BufferTooSmall,
+            EVRRenderModelError_VRRenderModelError_NotEnoughNormals,
+            EVRRenderModelError_VRRenderModelError_NotEnoughTexCoords,
+            EVRRenderModelError_VRRenderModelError_InvalidTexture]);
+    }
+
+    #[test]
+    fn describes_tracked_property_errors() {
+        assert_described(&[
+            ETrackedPropertyError_TrackedProp_Success,
+            ETrackedPropertyError_TrackedProp_WrongDataType,
+            ETrackedPropertyError_TrackedProp_WrongDeviceClass,
+            ETrackedPropertyError_TrackedProp_BufferTooSmall,
+            ETrackedPropertyError_TrackedProp_UnknownProperty,
+            ETrackedPropertyError_TrackedProp_InvalidDevice,
+            ETrackedPropertyError_TrackedProp_CouldNotContactServer,
+            ETrackedPropertyError_TrackedProp_ValueNotProvidedByDevice,
+            ETrackedPropertyError_TrackedProp_StringExceedsMaximumLength,
+            ETrackedPropertyError_TrackedProp_NotYetAvailable]);
+    }
+
+    #[test]
+    fn describes_overlay_errors() {
+        assert_described(&[
+            EVROverlayError_VROverlayError_None,
+            EVROverlayError_VROverlayError_UnknownOverlay,
+            EVROverlayError_VROverlayError_InvalidHandle,
+            EVROverlayError_VROverlayError_PermissionDenied,
+            EVROverlayError_VROverlayError_OverlayLimitExceeded,
+            EVROverlayError_VROverlayError_WrongVisibilityType,
+            EVROverlayError_VROverlayError_KeyTooLong,
+            EVROverlayError_VROverlayError_NameTooLong,
+            EVROverlayError_VROverlayError_KeyInUse,
+            EVROverlayError_VROverlayError_WrongTransformType,
+            EVROverlayError_VROverlayError_InvalidTrackedDevice,
+            EVROverlayError_VROverlayError_InvalidParameter,
+            EVROverlayError_VROverlayError_ThumbnailCantBeDestroyed,
+            EVROverlayError_VROverlayError_ArrayTooSmall,
+            EVROverlayError_VROverlayError_RequestFailed,
+            EVROverlayError_VROverlayError_InvalidTexture,
+            EVROverlayError_VROverlayError_UnableToLoadFile,
+            EVROverlayError_VROverlayError_KeyboardAlreadyInUse,
+            EVROverlayError_VROverlayError_NoNeighbor]);
+    }
+
+    #[test]
+    fn describes_application_errors() {
+        assert_described(&[
+            EVRApplicationError_VRApplicationError_None,
+            EVRApplicationError_VRApplicationError_AppKeyAlreadyExists,
+            EVRApplicationError_VRApplicationError_NoManifest,
+            EVRApplicationError_VRApplicationError_NoApplication,
+            EVRApplicationError_VRApplicationError_InvalidIndex,
+            EVRApplicationError_VRApplicationError_UnknownApplication,
+            EVRApplicationError_VRApplicationError_IPCFailed,
+            EVRApplicationError_VRApplicationError_ApplicationAlreadyRunning,
+            EVRApplicationError_VRApplicationError_InvalidManifest,
+            EVRApplicationError_VRApplicationError_InvalidApplication,
+            EVRApplicationError_VRApplicationError_LaunchFailed,
+            EVRApplicationError_VRApplicationError_ApplicationAlreadyStarting,
+            EVRApplicationError_VRApplicationError_LaunchInProgress,
+            EVRApplicationError_VRApplicationError_OldApplicationQuitting,
+            EVRApplicationError_VRApplicationError_TransitionAborted,
+            EVRApplicationError_VRApplicationError_IsTemplate,
+            EVRApplicationError_VRApplicationError_BufferTooSmall,
+            EVRApplicationError_VRApplicationError_PropertyNotSet,
+            EVRApplicationError_VRApplicationError_UnknownProperty]);
+    }
+
+    #[test]
+    fn describes_settings_errors() {
+        assert_described(&[
+            EVRSettingsError_VRSettingsError_None,
+            EVRSettingsError_VRSettingsError_IPCFailed,
+            EVRSettingsError_VRSettingsError_WriteFailed,
+            EVRSettingsError_VRSettingsError_ReadFailed]);
+    }
+
+    #[test]
+    fn display_uses_the_description() {
+        let err = Error::from_raw(EVRCompositorError_VRCompositorError_DoNotHaveFocus);
+        assert_eq!(err.to_string(), "application does not have focus");
+    }
+
+    #[test]
+    fn init_error_converts_into_boxed_error() {
+        fn init() -> Result<(), Error<EVRInitError>> {
+            Err(Error::from_raw(EVRInitError_VRInitError_Init_HmdNotFound))
+        }
+
+        fn run() -> Result<(), Box<dyn error::Error>> {
+            init()?;
+            Ok(())
+        }
+
+        assert_eq!(run().unwrap_err().to_string(), "hmd not found");
+    }
+
+    #[test]
+    fn init_error_none_is_ok() {
+        assert!(Error::from_raw(EVRInitError_VRInitError_None).is_ok());
+        assert!(!Error::from_raw(EVRInitError_VRInitError_None).is_err());
+        assert!(Error::from_raw(EVRInitError_VRInitError_Init_HmdNotFound).is_err());
+        assert!(!Error::from_raw(EVRInitError_VRInitError_Init_HmdNotFound).is_ok());
+    }
+}
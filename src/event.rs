@@ -0,0 +1,411 @@
+use openvr_sys;
+use openvr_sys::EVREventType::*;
+
+use std::fmt;
+
+use overlay::OverlayHandle;
+
+/// Describes the type of an openvr event
+///  events without a dedicated variant are reported as Other with the raw type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventType {
+    None,
+
+    TrackedDeviceActivated,
+    TrackedDeviceDeactivated,
+    TrackedDeviceUpdated,
+    TrackedDeviceUserInteractionStarted,
+    TrackedDeviceUserInteractionEnded,
+    IpdChanged,
+    EnterStandbyMode,
+    LeaveStandbyMode,
+    TrackedDeviceRoleChanged,
+
+    ButtonPress,
+    ButtonUnpress,
+    ButtonTouch,
+    ButtonUntouch,
+
+    MouseMove,
+    MouseButtonDown,
+    MouseButtonUp,
+    FocusEnter,
+    FocusLeave,
+    Scroll,
+    TouchPadMove,
+
+    InputFocusCaptured,
+    InputFocusReleased,
+    SceneFocusLost,
+    SceneFocusGained,
+    SceneApplicationChanged,
+    SceneFocusChanged,
+
+    OverlayShown,
+    OverlayHidden,
+    DashboardActivated,
+    DashboardDeactivated,
+    DashboardThumbSelected,
+    DashboardRequested,
+
+    Quit,
+    ProcessQuit,
+    QuitAbortedUserPrompt,
+    QuitAcknowledged,
+    DriverRequestedQuit,
+
+    ChaperoneDataHasChanged,
+    ChaperoneUniverseHasChanged,
+    ChaperoneTempDataHasChanged,
+    ChaperoneSettingsHaveChanged,
+    SeatedZeroPoseReset,
+
+    Other(u32)
+}
+
+// every event type with a dedicated variant, from_raw looks the raw type up in here
+const KNOWN_EVENT_TYPES: [EventType; 43] = [
+    EventType::None,
+    EventType::TrackedDeviceActivated,
+    EventType::TrackedDeviceDeactivated,
+    EventType::TrackedDeviceUpdated,
+    EventType::TrackedDeviceUserInteractionStarted,
+    EventType::TrackedDeviceUserInteractionEnded,
+    EventType::IpdChanged,
+    EventType::EnterStandbyMode,
+    EventType::LeaveStandbyMode,
+    EventType::TrackedDeviceRoleChanged,
+
+    EventType::ButtonPress,
+    EventType::ButtonUnpress,
+    EventType::ButtonTouch,
+    EventType::ButtonUntouch,
+
+    EventType::MouseMove,
+    EventType::MouseButtonDown,
+    EventType::MouseButtonUp,
+    EventType::FocusEnter,
+    EventType::FocusLeave,
+    EventType::Scroll,
+    EventType::TouchPadMove,
+
+    EventType::InputFocusCaptured,
+    EventType::InputFocusReleased,
+    EventType::SceneFocusLost,
+    EventType::SceneFocusGained,
+    EventType::SceneApplicationChanged,
+    EventType::SceneFocusChanged,
+
+    EventType::OverlayShown,
+    EventType::OverlayHidden,
+    EventType::DashboardActivated,
+    EventType::DashboardDeactivated,
+    EventType::DashboardThumbSelected,
+    EventType::DashboardRequested,
+
+    EventType::Quit,
+    EventType::ProcessQuit,
+    EventType::QuitAbortedUserPrompt,
+    EventType::QuitAcknowledged,
+    EventType::DriverRequestedQuit,
+
+    EventType::ChaperoneDataHasChanged,
+    EventType::ChaperoneUniverseHasChanged,
+    EventType::ChaperoneTempDataHasChanged,
+    EventType::ChaperoneSettingsHaveChanged,
+    EventType::SeatedZeroPoseReset
+];
+
+impl EventType {
+    /// Converts the raw event type of a VREvent_t, see EVREventType for the values
+    pub fn from_raw(raw: u32) -> Self {
+        KNOWN_EVENT_TYPES.iter()
+            .find(|event_type| event_type.to_raw() == raw)
+            .cloned()
+            .unwrap_or(EventType::Other(raw))
+    }
+
+    /// Converts the event type into the raw value of EVREventType
+    pub fn to_raw(self) -> u32 {
+        use self::EventType::*;
+
+        match self {
+            None => EVREventType_VREvent_None as u32,
+            TrackedDeviceActivated => EVREventType_VREvent_TrackedDeviceActivated as u32,
+            TrackedDeviceDeactivated => EVREventType_VREvent_TrackedDeviceDeactivated as u32,
+            TrackedDeviceUpdated => EVREventType_VREvent_TrackedDeviceUpdated as u32,
+            TrackedDeviceUserInteractionStarted => EVREventType_VREvent_TrackedDeviceUserInteractionStarted as u32,
+            TrackedDeviceUserInteractionEnded => EVREventType_VREvent_TrackedDeviceUserInteractionEnded as u32,
+            IpdChanged => EVREventType_VREvent_IpdChanged as u32,
+            EnterStandbyMode => EVREventType_VREvent_EnterStandbyMode as u32,
+            LeaveStandbyMode => EVREventType_VREvent_LeaveStandbyMode as u32,
+            TrackedDeviceRoleChanged => EVREventType_VREvent_TrackedDeviceRoleChanged as u32,
+
+            ButtonPress => EVREventType_VREvent_ButtonPress as u32,
+            ButtonUnpress => EVREventType_VREvent_ButtonUnpress as u32,
+            ButtonTouch => EVREventType_VREvent_ButtonTouch as u32,
+            ButtonUntouch => EVREventType_VREvent_ButtonUntouch as u32,
+
+            MouseMove => EVREventType_VREvent_MouseMove as u32,
+            MouseButtonDown => EVREventType_VREvent_MouseButtonDown as u32,
+            MouseButtonUp => EVREventType_VREvent_MouseButtonUp as u32,
+            FocusEnter => EVREventType_VREvent_FocusEnter as u32,
+            FocusLeave => EVREventType_VREvent_FocusLeave as u32,
+            Scroll => EVREventType_VREvent_Scroll as u32,
+            TouchPadMove => EVREventType_VREvent_TouchPadMove as u32,
+
+            InputFocusCaptured => EVREventType_VREvent_InputFocusCaptured as u32,
+            InputFocusReleased => EVREventType_VREvent_InputFocusReleased as u32,
+            SceneFocusLost => EVREventType_VREvent_SceneFocusLost as u32,
+            SceneFocusGained => EVREventType_VREvent_SceneFocusGained as u32,
+            SceneApplicationChanged => EVREventType_VREvent_SceneApplicationChanged as u32,
+            SceneFocusChanged => EVREventType_VREvent_SceneFocusChanged as u32,
+
+            OverlayShown => EVREventType_VREvent_OverlayShown as u32,
+            OverlayHidden => EVREventType_VREvent_OverlayHidden as u32,
+            DashboardActivated => EVREventType_VREvent_DashboardActivated as u32,
+            DashboardDeactivated => EVREventType_VREvent_DashboardDeactivated as u32,
+            DashboardThumbSelected => EVREventType_VREvent_DashboardThumbSelected as u32,
+            DashboardRequested => EVREventType_VREvent_DashboardRequested as u32,
+
+            Quit => EVREventType_VREvent_Quit as u32,
+            ProcessQuit => EVREventType_VREvent_ProcessQuit as u32,
+            QuitAbortedUserPrompt => EVREventType_VREvent_QuitAborted_UserPrompt as u32,
+            QuitAcknowledged => EVREventType_VREvent_QuitAcknowledged as u32,
+            DriverRequestedQuit => EVREventType_VREvent_DriverRequestedQuit as u32,
+
+            ChaperoneDataHasChanged => EVREventType_VREvent_ChaperoneDataHasChanged as u32,
+            ChaperoneUniverseHasChanged => EVREventType_VREvent_ChaperoneUniverseHasChanged as u32,
+            ChaperoneTempDataHasChanged => EVREventType_VREvent_ChaperoneTempDataHasChanged as u32,
+            ChaperoneSettingsHaveChanged => EVREventType_VREvent_ChaperoneSettingsHaveChanged as u32,
+            SeatedZeroPoseReset => EVREventType_VREvent_SeatedZeroPoseReset as u32,
+
+            Other(raw) => raw
+        }
+    }
+}
+
+/// The payload of an event, see Event::decode
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EventData {
+    /// button events, compare button with Button::id
+    Controller { button: u32 },
+    /// mouse events of overlays, in overlay coordinates
+    Mouse { x: f32, y: f32, button: u32 },
+    /// scroll events of overlays
+    Scroll { x: f32, y: f32, repeat_count: u32 },
+    /// touch pad events of overlays
+    TouchPadMove { finger_down: bool, seconds_finger_down: f32, x: f32, y: f32 },
+    /// focus, scene application and quit events
+    Process { pid: u32, old_pid: u32, forced: bool },
+    /// overlay and dashboard events
+    Overlay { handle: OverlayHandle },
+    Ipd { meters: f32 },
+    Chaperone { previous_universe: u64, current_universe: u64 },
+    /// the event has no payload (or it is not decoded yet)
+    None
+}
+
+/// An event received from openvr
+pub struct Event {
+    pub event_type: EventType,
+    pub tracked_device_index: u32,
+    /// seconds since the event happened
+    pub age: f32,
+    raw: openvr_sys::VREvent_t
+}
+
+impl Event {
+    pub fn from_raw(raw: openvr_sys::VREvent_t) -> Self {
+        Event {
+            event_type: EventType::from_raw(raw.eventType),
+            tracked_device_index: raw.trackedDeviceIndex,
+            age: raw.eventAgeSeconds,
+            raw: raw
+        }
+    }
+
+    /// Decodes the payload of the event, the union arm is selected by the event type
+    pub fn decode(&self) -> EventData {
+        use self::EventType::*;
+
+        let data = &self.raw.data as *const _;
+
+        unsafe {
+            match self.event_type {
+                ButtonPress | ButtonUnpress | ButtonTouch | ButtonUntouch => {
+                    let controller = &* { data as *const openvr_sys::VREvent_Controller_t };
+                    EventData::Controller { button: controller.button }
+                },
+                MouseMove | MouseButtonDown | MouseButtonUp => {
+                    let mouse = &* { data as *const openvr_sys::VREvent_Mouse_t };
+                    EventData::Mouse { x: mouse.x, y: mouse.y, button: mouse.button }
+                },
+                Scroll => {
+                    let scroll = &* { data as *const openvr_sys::VREvent_Scroll_t };
+                    EventData::Scroll { x: scroll.xdelta, y: scroll.ydelta, repeat_count: scroll.repeatCount }
+                },
+                TouchPadMove => {
+                    let touch = &* { data as *const openvr_sys::VREvent_TouchPadMove_t };
+                    EventData::TouchPadMove {
+                        finger_down: touch.bFingerDown > 0,
+                        seconds_finger_down: touch.flSecondsFingerDown,
+                        x: touch.fValueXRaw,
+                        y: touch.fValueYRaw
+                    }
+                },
+                InputFocusCaptured | InputFocusReleased | SceneFocusLost | SceneFocusGained |
+                SceneApplicationChanged | SceneFocusChanged |
+                Quit | ProcessQuit | QuitAbortedUserPrompt | QuitAcknowledged => {
+                    let process = &* { data as *const openvr_sys::VREvent_Process_t };
+                    EventData::Process { pid: process.pid, old_pid: process.oldPid, forced: process.bForced > 0 }
+                },
+                OverlayShown | OverlayHidden | DashboardActivated | DashboardDeactivated |
+                DashboardThumbSelected | DashboardRequested => {
+                    let overlay = &* { data as *const openvr_sys::VREvent_Overlay_t };
+                    EventData::Overlay { handle: OverlayHandle::from_raw(overlay.overlayHandle) }
+                },
+                IpdChanged => {
+                    let ipd = &* { data as *const openvr_sys::VREvent_Ipd_t };
+                    EventData::Ipd { meters: ipd.ipdMeters }
+                },
+                ChaperoneUniverseHasChanged => {
+                    let chaperone = &* { data as *const openvr_sys::VREvent_Chaperone_t };
+                    EventData::Chaperone {
+                        previous_universe: chaperone.m_nPreviousUniverse,
+                        current_universe: chaperone.m_nCurrentUniverse
+                    }
+                },
+                _ => EventData::None
+            }
+        }
+    }
+
+    /// Returns the mouse position of mouse events in overlay coordinates
+    ///  None for all other events
+    pub fn mouse_position(&self) -> Option<(f32, f32)> {
+        match self.decode() {
+            EventData::Mouse { x, y, .. } => Some((x, y)),
+            _ => None
+        }
+    }
+
+    /// Returns the underlying openvr event
+    pub fn to_raw(&self) -> &openvr_sys::VREvent_t {
+        &self.raw
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Event {{ event_type: {:?}, tracked_device_index: {}, age: {} }}",
+               self.event_type, self.tracked_device_index, self.age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_event_types_round_trip() {
+        for &event_type in KNOWN_EVENT_TYPES.iter() {
+            assert_eq!(EventType::from_raw(event_type.to_raw()), event_type);
+        }
+    }
+
+    #[test]
+    fn raw_values_match_openvr() {
+        assert_eq!(EventType::from_raw(0), EventType::None);
+        assert_eq!(EventType::from_raw(200), EventType::ButtonPress);
+        assert_eq!(EventType::from_raw(702), EventType::QuitAbortedUserPrompt);
+        assert_eq!(EventType::SeatedZeroPoseReset.to_raw(), 804);
+    }
+
+    #[test]
+    fn unknown_event_types_are_kept() {
+        assert_eq!(EventType::from_raw(1234567), EventType::Other(1234567));
+        assert_eq!(EventType::Other(1234567).to_raw(), 1234567);
+    }
+
+    // builds an event with the payload written into the data union
+    fn event<T>(event_type: EventType, payload: T) -> Event {
+        unsafe {
+            let mut raw: openvr_sys::VREvent_t = ::std::mem::zeroed();
+            assert!(::std::mem::size_of::<T>() <= ::std::mem::size_of_val(&raw.data));
+            raw.eventType = event_type.to_raw();
+            ::std::ptr::write(&mut raw.data as *mut _ as *mut T, payload);
+            Event::from_raw(raw)
+        }
+    }
+
+    fn zeroed<T>() -> T {
+        unsafe { ::std::mem::zeroed() }
+    }
+
+    #[test]
+    fn decode_reads_the_input_payloads() {
+        let mut controller: openvr_sys::VREvent_Controller_t = zeroed();
+        controller.button = 33;
+        assert_eq!(event(EventType::ButtonPress, controller).decode(), EventData::Controller { button: 33 });
+
+        let mut mouse: openvr_sys::VREvent_Mouse_t = zeroed();
+        mouse.x = 0.25;
+        mouse.y = 0.5;
+        mouse.button = 1;
+        assert_eq!(event(EventType::MouseButtonDown, mouse).decode(), EventData::Mouse { x: 0.25, y: 0.5, button: 1 });
+
+        let mut scroll: openvr_sys::VREvent_Scroll_t = zeroed();
+        scroll.xdelta = -1.0;
+        scroll.ydelta = 2.0;
+        scroll.repeatCount = 3;
+        assert_eq!(event(EventType::Scroll, scroll).decode(), EventData::Scroll { x: -1.0, y: 2.0, repeat_count: 3 });
+
+        let mut touch: openvr_sys::VREvent_TouchPadMove_t = zeroed();
+        touch.bFingerDown = 1;
+        touch.flSecondsFingerDown = 1.5;
+        touch.fValueXRaw = 0.125;
+        touch.fValueYRaw = -0.75;
+        assert_eq!(event(EventType::TouchPadMove, touch).decode(), EventData::TouchPadMove {
+            finger_down: true,
+            seconds_finger_down: 1.5,
+            x: 0.125,
+            y: -0.75
+        });
+    }
+
+    #[test]
+    fn decode_reads_the_runtime_payloads() {
+        let mut process: openvr_sys::VREvent_Process_t = zeroed();
+        process.pid = 42;
+        process.oldPid = 7;
+        process.bForced = 1;
+        assert_eq!(event(EventType::Quit, process).decode(), EventData::Process { pid: 42, old_pid: 7, forced: true });
+
+        let mut overlay: openvr_sys::VREvent_Overlay_t = zeroed();
+        overlay.overlayHandle = 17;
+        assert_eq!(event(EventType::DashboardActivated, overlay).decode(), EventData::Overlay { handle: OverlayHandle(17) });
+
+        let mut ipd: openvr_sys::VREvent_Ipd_t = zeroed();
+        ipd.ipdMeters = 0.0625;
+        assert_eq!(event(EventType::IpdChanged, ipd).decode(), EventData::Ipd { meters: 0.0625 });
+
+        let mut chaperone: openvr_sys::VREvent_Chaperone_t = zeroed();
+        chaperone.m_nPreviousUniverse = 1;
+        chaperone.m_nCurrentUniverse = 2;
+        assert_eq!(event(EventType::ChaperoneUniverseHasChanged, chaperone).decode(), EventData::Chaperone {
+            previous_universe: 1,
+            current_universe: 2
+        });
+    }
+
+    #[test]
+    fn decode_ignores_events_without_payload() {
+        let mut controller: openvr_sys::VREvent_Controller_t = zeroed();
+        controller.button = 33;
+
+        assert_eq!(event(EventType::SeatedZeroPoseReset, controller).decode(), EventData::None);
+        assert_eq!(event(EventType::Other(1234567), controller).decode(), EventData::None);
+        assert_eq!(event(EventType::SeatedZeroPoseReset, controller).mouse_position(), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use render_models::*;
+
+// size of a single interleaved vertex: position (3 floats), normal (3 floats), uv (2 floats)
+const VERTEX_STRIDE: usize = 32;
+
+const BASE64_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// encodes bytes as base64, used to embed buffers and images into the gltf file
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
+        let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(BASE64_ALPHABET[(triple >> 18) as usize & 63] as char);
+        out.push(BASE64_ALPHABET[(triple >> 12) as usize & 63] as char);
+        if chunk.len() > 1 {
+            out.push(BASE64_ALPHABET[(triple >> 6) as usize & 63] as char);
+        } else {
+            out.push('=');
+        }
+        if chunk.len() > 2 {
+            out.push(BASE64_ALPHABET[triple as usize & 63] as char);
+        } else {
+            out.push('=');
+        }
+    }
+    out
+}
+
+fn push_f32(buffer: &mut Vec<u8>, value: f32) {
+    buffer.extend_from_slice(&value.to_bits().to_le_bytes());
+}
+
+impl RenderModel {
+    /// Writes the model as a single mesh into a gltf 2.0 file, requires the `gltf-export` feature
+    ///  all data (including the optional texture) is embedded into the file
+    ///  fails with InvalidInput for a model without triangles and with InvalidData when a vertex
+    ///  holds a nan or infinite value, as neither can be represented in a valid gltf file
+    ///  this inherent method is the whole api of the export, there is no gltf module to import
+    pub fn export_gltf(&self, texture: Option<&RenderModelTexture>, path: &Path) -> io::Result<()> {
+        if self.vertex_count() == 0 || self.triangle_count() == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "render model has no triangles to export"));
+        }
+
+        // interleaved vertices followed by the indices
+        let mut buffer: Vec<u8> = Vec::with_capacity(self.vertex_count() * VERTEX_STRIDE + self.triangle_count() * 6);
+        for vertex in self.vertices() {
+            for value in vertex.position().iter().chain(vertex.normal().iter()).chain(vertex.tex_coord().iter()) {
+                if !value.is_finite() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "render model has a non finite vertex value"));
+                }
+                push_f32(&mut buffer, *value);
+            }
+        }
+        let vertex_length = buffer.len();
+        for index in self.index_iter() {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        let index_length = buffer.len() - vertex_length;
+        while buffer.len() % 4 != 0 {
+            buffer.push(0);
+        }
+
+        let (min, max) = self.bounds();
+
+        let mut json = String::new();
+        json.push_str("{\"asset\":{\"version\":\"2.0\",\"generator\":\"rust-openvr\"},");
+        json.push_str("\"scene\":0,\"scenes\":[{\"nodes\":[0]}],\"nodes\":[{\"mesh\":0}],");
+        json.push_str("\"meshes\":[{\"primitives\":[{\"attributes\":{\"POSITION\":0,\"NORMAL\":1,\"TEXCOORD_0\":2},\"indices\":3");
+        if texture.is_some() {
+            json.push_str(",\"material\":0");
+        }
+        json.push_str("}]}],");
+        json.push_str(&format!("\"buffers\":[{{\"byteLength\":{},\"uri\":\"data:application/octet-stream;base64,{}\"}}],",
+                               buffer.len(), base64(&buffer)));
+        json.push_str(&format!("\"bufferViews\":[{{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{},\"byteStride\":{},\"target\":34962}},\
+                                {{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}],",
+                               vertex_length, VERTEX_STRIDE, vertex_length, index_length));
+        json.push_str(&format!("\"accessors\":[\
+                                {{\"bufferView\":0,\"byteOffset\":0,\"componentType\":5126,\"count\":{0},\"type\":\"VEC3\",\"min\":[{1},{2},{3}],\"max\":[{4},{5},{6}]}},\
+                                {{\"bufferView\":0,\"byteOffset\":12,\"componentType\":5126,\"count\":{0},\"type\":\"VEC3\"}},\
+                                {{\"bufferView\":0,\"byteOffset\":24,\"componentType\":5126,\"count\":{0},\"type\":\"VEC2\"}},\
+                                {{\"bufferView\":1,\"byteOffset\":0,\"componentType\":5123,\"count\":{7},\"type\":\"SCALAR\"}}]",
+                               self.vertex_count(), min[0], min[1], min[2], max[0], max[1], max[2], self.triangle_count() * 3));
+
+        if let Some(texture) = texture {
+            let mut png = Vec::new();
+            texture.write_png(&mut png)?;
+            json.push_str(&format!(",\"images\":[{{\"uri\":\"data:image/png;base64,{}\"}}]", base64(&png)));
+            json.push_str(",\"samplers\":[{}],\"textures\":[{\"source\":0,\"sampler\":0}]");
+            json.push_str(",\"materials\":[{\"pbrMetallicRoughness\":{\"baseColorTexture\":{\"index\":0},\"metallicFactor\":0.0}}]");
+        }
+        json.push_str("}");
+
+        let mut file = File::create(path)?;
+        file.write_all(json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gltf;
+    use mock::MockRenderModel;
+    use serde_json;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    fn decode_base64(text: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut bits = 0u32;
+        let mut count = 0;
+        for c in text.bytes().take_while(|&c| c != b'=') {
+            let value = BASE64_ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+            bits = (bits << 6) | value;
+            count += 6;
+            if count >= 8 {
+                count -= 8;
+                out.push((bits >> count) as u8);
+            }
+        }
+        out
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("openvr-{}-{}.gltf", name, process::id()))
+    }
+
+    fn triangle(position: [f32; 3]) -> MockRenderModel {
+        MockRenderModel::new(vec![
+            MockRenderModel::vertex(position, [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+            MockRenderModel::vertex([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0])
+        ], vec![0, 1, 2])
+    }
+
+    #[test]
+    fn base64_matches_the_rfc_examples() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(decode_base64(&base64(b"foobar")), b"foobar");
+    }
+
+    #[test]
+    fn export_writes_parsable_gltf() {
+        let data = triangle([0.0, 0.0, 0.0]);
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let path = temp_path("export");
+
+        model.export_gltf(None, &path).unwrap();
+        let file = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // the gltf crate validates the document against the specification
+        let document = gltf::Gltf::from_slice(&file).unwrap();
+        let primitive = document.meshes().next().unwrap().primitives().next().unwrap();
+        assert_eq!(document.meshes().count(), 1);
+        assert_eq!(primitive.get(&gltf::Semantic::Positions).unwrap().count(), 3);
+        assert_eq!(primitive.get(&gltf::Semantic::Normals).unwrap().count(), 3);
+        assert_eq!(primitive.get(&gltf::Semantic::TexCoords(0)).unwrap().count(), 3);
+        assert_eq!(primitive.indices().unwrap().count(), 3);
+
+        let json: serde_json::Value = serde_json::from_slice(&file).unwrap();
+
+        let buffer = &json["buffers"][0];
+        let uri = buffer["uri"].as_str().unwrap();
+        let bytes = decode_base64(uri.trim_start_matches("data:application/octet-stream;base64,"));
+        assert_eq!(buffer["byteLength"].as_u64().unwrap() as usize, bytes.len());
+
+        // 3 vertices of 32 bytes, 3 indices of 2 bytes padded to 4 byte alignment
+        assert_eq!(bytes.len(), 3 * VERTEX_STRIDE + 8);
+        assert_eq!(json["accessors"][0]["count"], 3);
+        assert_eq!(json["accessors"][3]["count"], 3);
+        let max: Vec<f64> = json["accessors"][0]["max"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect();
+        assert_eq!(max, vec![1.0, 1.0, 0.0]);
+        assert_eq!(bytes[3 * VERTEX_STRIDE..3 * VERTEX_STRIDE + 6], [0, 0, 1, 0, 2, 0]);
+    }
+
+    #[test]
+    fn export_rejects_an_empty_model() {
+        let data = MockRenderModel::new(Vec::new(), Vec::new());
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let path = temp_path("empty");
+
+        let err = model.export_gltf(None, &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn export_rejects_non_finite_values() {
+        let data = triangle([::std::f32::NAN, 0.0, 0.0]);
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let path = temp_path("nan");
+
+        let err = model.export_gltf(None, &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!path.exists());
+    }
+}
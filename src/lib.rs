@@ -1,62 +1,86 @@
 extern crate openvr_sys;
+#[cfg(feature = "image")]
+extern crate image;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
+#[cfg(feature = "glam")]
+extern crate glam;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(test)]
+extern crate serde_json;
+#[cfg(all(test, feature = "gltf-export"))]
+extern crate gltf;
 
-use openvr_sys::EVRInitError::*;
-use openvr_sys::EVRApplicationType::*;
+#[cfg(test)]
+#[macro_use]
+mod stub;
 
 pub mod common;
+pub mod constants;
 pub mod error;
 pub mod tracking;
+pub mod event;
+pub mod math;
 pub mod system;
 pub mod extended_display;
 pub mod compositor;
 pub mod render_models;
 pub mod tracked_camera;
+pub mod chaperone;
+pub mod chaperone_setup;
+pub mod overlay;
+pub mod applications;
+pub mod settings;
 pub mod subsystems;
+pub mod context;
+// only adds RenderModel::export_gltf, so the module itself stays private
+#[cfg(feature = "gltf-export")]
+mod gltf_export;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 pub use system::IVRSystem;
 pub use extended_display::IVRExtendedDisplay;
 pub use compositor::IVRCompositor;
 pub use render_models::IVRRenderModels;
 pub use tracked_camera::IVRTrackedCamera;
+pub use chaperone::IVRChaperone;
+pub use chaperone_setup::IVRChaperoneSetup;
+pub use overlay::IVROverlay;
+pub use applications::IVRApplications;
+pub use settings::IVRSettings;
+pub use context::Context;
+pub use context::ContextError;
 
 pub use subsystems::*;
 pub use error::*;
 
 pub use common::Eye;
+pub use common::ApplicationType;
 
 /// Inits the open vr interface and returns the system
+///  calling it again while the runtime is initialized returns the system of the running runtime
+///  fails with VRInitError_Unknown while a Context owns the runtime, use Context::init to tell that case apart
 pub fn init() ->  Result<system::IVRSystem, Error<openvr_sys::EVRInitError>> {
-    let mut err = EVRInitError_VRInitError_None;
-    let app_type = EVRApplicationType_VRApplication_Scene;
-
     // try to initialize base vr eco
-    unsafe {
-        openvr_sys::VR_InitInternal(&mut err, app_type);
-    };
-
-    // check for errors
-    match err {
-        EVRInitError_VRInitError_None => {
-            // get system
-            let result = system();
-            match result {
-                Ok(sys) => {
-                    return Ok(sys);
-                },
-                Err(err) => {
-                    return Err(err);
-                }
-            }
+    match context::init_runtime(ApplicationType::Scene, context::Owner::Free) {
+        Ok(()) => {},
+        Err(ContextError::AlreadyInitialized) => {
+            return Err(Error::from_raw(openvr_sys::EVRInitError::EVRInitError_VRInitError_Unknown));
         },
-        _ => {
-            return Err(Error::from_raw(err));
+        Err(ContextError::Init(err)) => {
+            return Err(err);
         }
-    };
+    }
+
+    // get system
+    system()
 }
 
 /// Shutdowns all openvr related systems
+///  does nothing when openvr is not initialized or when a Context owns the runtime
 pub fn shutdown() {
-    unsafe {
-        openvr_sys::VR_ShutdownInternal();
-    }
+    context::shutdown_runtime(context::Owner::Free);
 }
@@ -0,0 +1,243 @@
+use openvr_sys;
+
+#[cfg(feature = "glam")]
+use glam;
+#[cfg(feature = "glam")]
+use tracking::TrackedDevicePose;
+
+/// Converts a raw 3x4 matrix into a row major array, mat[row][column]
+pub fn mat34_to_array(m: &openvr_sys::HmdMatrix34_t) -> [[f32; 4]; 3] {
+    m.m
+}
+
+/// Converts a raw 4x4 matrix into a row major array, mat[row][column]
+pub fn mat44_to_array(m: &openvr_sys::HmdMatrix44_t) -> [[f32; 4]; 4] {
+    m.m
+}
+
+/// Converts a row major array into a raw 3x4 matrix
+pub fn array_to_mat34(m: &[[f32; 4]; 3]) -> openvr_sys::HmdMatrix34_t {
+    openvr_sys::HmdMatrix34_t { m: *m }
+}
+
+/// Extends a row major 3x4 matrix with the row [0, 0, 0, 1]
+pub fn mat34_to_mat44(m: &[[f32; 4]; 3]) -> [[f32; 4]; 4] {
+    [m[0], m[1], m[2], [0.0, 0.0, 0.0, 1.0]]
+}
+
+/// Converts a row major 3x4 matrix into a column major 4x4 matrix as expected by opengl, mat[column][row]
+pub fn mat34_to_column_major(m: &[[f32; 4]; 3]) -> [[f32; 4]; 4] {
+    mat44_to_column_major(&mat34_to_mat44(m))
+}
+
+/// Converts a row major 4x4 matrix into a column major one as expected by opengl, mat[column][row]
+pub fn mat44_to_column_major(m: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut out = [[0.0; 4]; 4];
+    for row in 0..4 {
+        for column in 0..4 {
+            out[column][row] = m[row][column];
+        }
+    }
+    out
+}
+
+/// Inverts a row major 3x4 matrix
+///  assumes a rigid transform (rotation and translation only) as used by all openvr poses
+pub fn invert_mat34(m: &[[f32; 4]; 3]) -> [[f32; 4]; 3] {
+    let mut out = [[0.0; 4]; 3];
+
+    // the inverse rotation is the transposed rotation
+    for row in 0..3 {
+        for column in 0..3 {
+            out[row][column] = m[column][row];
+        }
+    }
+
+    // the inverse translation is the negated translation rotated back
+    for row in 0..3 {
+        out[row][3] = -(out[row][0] * m[0][3] + out[row][1] * m[1][3] + out[row][2] * m[2][3]);
+    }
+
+    out
+}
+
+/// Inverts a general row major 4x4 matrix, None when the matrix is singular
+pub fn invert_mat44(m: &[[f32; 4]; 4]) -> Option<[[f32; 4]; 4]> {
+    // gauss-jordan elimination with partial pivoting on [m | identity]
+    let mut a = *m;
+    let mut out = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+    // pivots below the rounding error of the largest entry are treated as zero
+    let largest = m.iter().flat_map(|row| row.iter()).fold(0.0f32, |max, v| max.max(v.abs()));
+    let threshold = ::std::f32::EPSILON * largest * 4.0;
+
+    for column in 0..4 {
+        let mut pivot = column;
+        for row in (column + 1)..4 {
+            if a[row][column].abs() > a[pivot][column].abs() {
+                pivot = row;
+            }
+        }
+        if a[pivot][column].abs() <= threshold {
+            return None;
+        }
+        a.swap(column, pivot);
+        out.swap(column, pivot);
+
+        let scale = 1.0 / a[column][column];
+        for i in 0..4 {
+            a[column][i] *= scale;
+            out[column][i] *= scale;
+        }
+
+        for row in 0..4 {
+            if row != column {
+                let factor = a[row][column];
+                for i in 0..4 {
+                    a[row][i] -= factor * a[column][i];
+                    out[row][i] -= factor * out[column][i];
+                }
+            }
+        }
+    }
+
+    Some(out)
+}
+
+/// Converts a row major 3x4 matrix into a glam matrix, requires the `glam` feature
+#[cfg(feature = "glam")]
+pub fn mat34_to_glam(m: &[[f32; 4]; 3]) -> glam::Mat4 {
+    glam::Mat4::from_cols_array_2d(&mat34_to_column_major(m))
+}
+
+/// Converts a row major 4x4 matrix into a glam matrix, requires the `glam` feature
+#[cfg(feature = "glam")]
+pub fn mat44_to_glam(m: &[[f32; 4]; 4]) -> glam::Mat4 {
+    glam::Mat4::from_cols_array_2d(&mat44_to_column_major(m))
+}
+
+#[cfg(feature = "glam")]
+impl From<TrackedDevicePose> for glam::Mat4 {
+    fn from(pose: TrackedDevicePose) -> Self {
+        mat34_to_glam(&pose.to_device)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<TrackedDevicePose> for glam::Affine3A {
+    fn from(pose: TrackedDevicePose) -> Self {
+        let m = pose.to_device;
+        glam::Affine3A::from_cols_array_2d(&[
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+            [m[0][3], m[1][3], m[2][3]],
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: [[f32; 4]; 4] = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+    fn assert_close(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) {
+        for row in 0..4 {
+            for column in 0..4 {
+                assert!((a[row][column] - b[row][column]).abs() < 1e-5, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    fn mul_mat44(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+        let mut out = [[0.0; 4]; 4];
+        for row in 0..4 {
+            for column in 0..4 {
+                out[row][column] = (0..4).map(|i| a[row][i] * b[i][column]).sum();
+            }
+        }
+        out
+    }
+
+    // rotation of 90 degrees around y with a translation of (1, 2, 3)
+    const RIGID: [[f32; 4]; 3] = [[0.0, 0.0, 1.0, 1.0], [0.0, 1.0, 0.0, 2.0], [-1.0, 0.0, 0.0, 3.0]];
+
+    #[test]
+    fn raw_conversions_keep_row_major_order() {
+        let raw = array_to_mat34(&RIGID);
+        assert_eq!(mat34_to_array(&raw), RIGID);
+
+        let raw = openvr_sys::HmdMatrix44_t { m: IDENTITY };
+        assert_eq!(mat44_to_array(&raw), IDENTITY);
+    }
+
+    #[test]
+    fn column_major_transposes() {
+        let m = mat34_to_column_major(&RIGID);
+        assert_eq!(m[3], [1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(m[0], [0.0, 0.0, -1.0, 0.0]);
+        assert_eq!(mat44_to_column_major(&mat44_to_column_major(&mat34_to_mat44(&RIGID))), mat34_to_mat44(&RIGID));
+    }
+
+    #[test]
+    fn invert_mat34_undoes_a_rigid_transform() {
+        let inverse = mat34_to_mat44(&invert_mat34(&RIGID));
+        assert_close(&mul_mat44(&inverse, &mat34_to_mat44(&RIGID)), &IDENTITY);
+    }
+
+    #[test]
+    fn invert_mat44_of_identity_is_identity() {
+        assert_eq!(invert_mat44(&IDENTITY), Some(IDENTITY));
+    }
+
+    #[test]
+    fn invert_mat44_of_general_matrix() {
+        let m = [[2.0, 0.0, 0.0, 1.0], [0.0, 0.5, 0.0, 0.0], [1.0, 0.0, 4.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+        let inverse = invert_mat44(&m).unwrap();
+        assert_close(&mul_mat44(&m, &inverse), &IDENTITY);
+    }
+
+    #[test]
+    fn invert_mat44_of_singular_matrix_is_none() {
+        let m = [[1.0, 2.0, 3.0, 4.0], [2.0, 4.0, 6.0, 8.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+        assert_eq!(invert_mat44(&m), None);
+        assert_eq!(invert_mat44(&[[0.0; 4]; 4]), None);
+    }
+
+    #[test]
+    fn invert_mat44_works_at_small_scales() {
+        // a fixed threshold would reject this well conditioned matrix
+        let mut m = IDENTITY;
+        for row in m.iter_mut() {
+            for v in row.iter_mut() {
+                *v *= 1e-7;
+            }
+        }
+        let inverse = invert_mat44(&m).unwrap();
+        assert_close(&mul_mat44(&m, &inverse), &IDENTITY);
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn glam_conversions_keep_rotation_and_translation() {
+        // rotates +x onto -z (90 degrees around y), then moves by (1, 2, 3)
+        let m = [[0.0, 0.0, 1.0, 1.0], [0.0, 1.0, 0.0, 2.0], [-1.0, 0.0, 0.0, 3.0]];
+        let mut raw: openvr_sys::TrackedDevicePose_t = unsafe { ::std::mem::zeroed() };
+        raw.mDeviceToAbsoluteTracking.m = m;
+        let pose = TrackedDevicePose::from_raw(0, raw);
+
+        let mat = mat34_to_glam(&m);
+        assert_eq!(mat.row(0), glam::Vec4::new(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(mat.row(2), glam::Vec4::new(-1.0, 0.0, 0.0, 3.0));
+        assert_eq!(mat.row(3), glam::Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(mat.transform_point3(glam::Vec3::X), glam::Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(glam::Mat4::from(pose), mat);
+
+        let affine = glam::Affine3A::from(pose);
+        assert_eq!(glam::Vec3::from(affine.translation), glam::Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(affine.transform_point3(glam::Vec3::X), glam::Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(affine.transform_vector3(glam::Vec3::Z), glam::Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(glam::Mat4::from(affine), mat);
+    }
+}
@@ -0,0 +1,520 @@
+//! A scripted stand-in for the openvr runtime, requires the `mock` feature
+//!  the subsystems returned by MockRuntime call into stub function tables instead of openvr,
+//!  so code using them can run without a runtime (for example in tests on ci)
+//!
+//! Scripted for IVRRenderModels are the render model names, their thumbnail urls and original
+//!  paths, components (name, render model and button mask) and the results of LoadRenderModel_Async
+//!  and LoadTexture_Async. Render models
+//!  and textures dropped while the mock is alive are freed through it. Every other entry of the
+//!  function table is empty, calling a method using one of them panics on the unwrap of the entry
+//!  (for example IVRRenderModels::component_state).
+//!
+//! The script is kept per thread, only one MockRuntime should be alive on a thread at a time.
+//!  Render models loaded through the mock point into its data, drop them before the mock.
+
+use openvr_sys;
+use openvr_sys::EVRRenderModelError;
+use openvr_sys::EVRRenderModelError::*;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_char;
+use std::ptr;
+
+use render_models::IVRRenderModels;
+use subsystems::Subsystem;
+
+/// Render model data owned by the mock, handed out by LoadRenderModel_Async
+///  can also be wrapped directly with RenderModel::from_raw
+pub struct MockRenderModel {
+    _vertices: Vec<openvr_sys::RenderModel_Vertex_t>,
+    _indices: Vec<u16>,
+    raw: Box<openvr_sys::RenderModel_t>
+}
+
+impl MockRenderModel {
+    /// Creates a model from vertices and triangle indices (three per triangle)
+    pub fn new(vertices: Vec<openvr_sys::RenderModel_Vertex_t>, indices: Vec<u16>) -> Self {
+        let mut raw: openvr_sys::RenderModel_t = unsafe { mem::zeroed() };
+        raw.rVertexData = vertices.as_ptr() as *mut _;
+        raw.unVertexCount = vertices.len() as u32;
+        raw.rIndexData = indices.as_ptr() as *mut _;
+        raw.unTriangleCount = (indices.len() / 3) as u32;
+        raw.diffuseTextureId = -1;
+
+        MockRenderModel {
+            _vertices: vertices,
+            _indices: indices,
+            raw: Box::new(raw)
+        }
+    }
+
+    /// Sets the id reported by RenderModel::diffuse_texture_id, models start without a texture (-1)
+    pub fn diffuse_texture(mut self, id: i32) -> Self {
+        self.raw.diffuseTextureId = id;
+        self
+    }
+
+    /// Creates a raw vertex
+    pub fn vertex(position: [f32; 3], normal: [f32; 3], tex_coord: [f32; 2]) -> openvr_sys::RenderModel_Vertex_t {
+        let mut vertex: openvr_sys::RenderModel_Vertex_t = unsafe { mem::zeroed() };
+        vertex.vPosition.v = position;
+        vertex.vNormal.v = normal;
+        vertex.rfTextureCoord = tex_coord;
+        vertex
+    }
+
+    /// Returns the raw model, it is valid as long as the mock model is alive
+    pub fn as_raw(&self) -> *mut openvr_sys::RenderModel_t {
+        &*self.raw as *const openvr_sys::RenderModel_t as *mut openvr_sys::RenderModel_t
+    }
+}
+
+/// Texture data owned by the mock, wrap it with RenderModelTexture::from_raw
+pub struct MockTexture {
+    _data: Vec<u8>,
+    raw: Box<openvr_sys::RenderModel_TextureMap_t>
+}
+
+impl MockTexture {
+    /// Creates a texture from rgba8 pixel data (row by row, 4 bytes per pixel)
+    pub fn new(width: u16, height: u16, data: Vec<u8>) -> Self {
+        assert_eq!(data.len(), width as usize * height as usize * 4);
+
+        let mut raw: openvr_sys::RenderModel_TextureMap_t = unsafe { mem::zeroed() };
+        raw.unWidth = width as _;
+        raw.unHeight = height as _;
+        raw.rubTextureMapData = data.as_ptr() as *mut _;
+
+        MockTexture {
+            _data: data,
+            raw: Box::new(raw)
+        }
+    }
+
+    /// Returns the raw texture, it is valid as long as the mock texture is alive
+    pub fn as_raw(&self) -> *mut openvr_sys::RenderModel_TextureMap_t {
+        &*self.raw as *const openvr_sys::RenderModel_TextureMap_t as *mut openvr_sys::RenderModel_TextureMap_t
+    }
+}
+
+enum MockLoad<T> {
+    Ready(T),
+    /// Loading for the given number of polls, then Ready
+    Delayed(usize, T),
+    Loading,
+    Failed(EVRRenderModelError)
+}
+
+impl<T> MockLoad<T> {
+    // answers a poll like LoadRenderModel_Async and LoadTexture_Async
+    fn poll(&mut self) -> Result<&T, EVRRenderModelError> {
+        match *self {
+            MockLoad::Ready(ref data) => Ok(data),
+            MockLoad::Delayed(ref mut polls, ref data) => {
+                if *polls > 0 {
+                    *polls -= 1;
+                    Err(EVRRenderModelError_VRRenderModelError_Loading)
+                } else {
+                    Ok(data)
+                }
+            },
+            MockLoad::Loading => Err(EVRRenderModelError_VRRenderModelError_Loading),
+            MockLoad::Failed(err) => Err(err)
+        }
+    }
+}
+
+struct MockComponent {
+    name: Vec<u8>,
+    render_model_name: Option<String>,
+    button_mask: u64
+}
+
+#[derive(Default)]
+struct Script {
+    render_model_names: Vec<String>,
+    components: HashMap<String, Vec<MockComponent>>,
+    loads: HashMap<String, MockLoad<MockRenderModel>>,
+    textures: HashMap<i32, MockLoad<MockTexture>>,
+    thumbnail_urls: HashMap<String, String>,
+    original_paths: HashMap<String, String>,
+    load_calls: usize,
+    freed_render_models: usize,
+    freed_textures: usize
+}
+
+impl Script {
+    fn component(&self, model_name: *mut c_char, component_name: *mut c_char) -> Option<&MockComponent> {
+        let model_name = unsafe { CStr::from_ptr(model_name) }.to_string_lossy();
+        let component_name = unsafe { CStr::from_ptr(component_name) }.to_bytes();
+
+        self.components.get(&*model_name)
+            .and_then(|components| components.iter().find(|component| &component.name[..] == component_name))
+    }
+}
+
+// the scripted data is kept per thread, so parallel tests don't interfere
+thread_local! {
+    static SCRIPT: RefCell<Script> = RefCell::new(Script::default());
+    static CURRENT_RENDER_MODELS: Cell<*const ()> = Cell::new(ptr::null());
+}
+
+/// Returns the render models subsystem of the mock alive on this thread
+///  used to free render models and textures through the mock
+pub(crate) fn current_render_models() -> Option<IVRRenderModels> {
+    let current = CURRENT_RENDER_MODELS.with(|current| current.get());
+    if current.is_null() {
+        None
+    } else {
+        unsafe { Some(IVRRenderModels::from_raw(current)) }
+    }
+}
+
+// like openvr, return the required size including the terminator when the buffer is too small
+unsafe fn write_string(value: &[u8], buffer: *mut c_char, len: u32) -> u32 {
+    let required = value.len() as u32 + 1;
+    if buffer.is_null() || len < required {
+        return required;
+    }
+
+    ptr::copy_nonoverlapping(value.as_ptr(), buffer as *mut u8, value.len());
+    *buffer.add(value.len()) = 0;
+    required
+}
+
+// answers a query about a render model like openvr: unknown models are an error, known models
+//  without the value get an empty string, a too small buffer is reported next to the required size
+unsafe fn write_model_string(script: &Script, name: *mut c_char, value: Option<&String>, buffer: *mut c_char, len: u32, err: *mut EVRRenderModelError) -> u32 {
+    let name = CStr::from_ptr(name).to_string_lossy();
+    if !script.render_model_names.iter().any(|known| *known == *name) {
+        *err = EVRRenderModelError_VRRenderModelError_InvalidModel;
+        return 0;
+    }
+
+    let value = value.map_or(&b""[..], |value| value.as_bytes());
+    let required = write_string(value, buffer, len);
+    *err = if required > len {
+        EVRRenderModelError_VRRenderModelError_BufferTooSmall
+    } else {
+        EVRRenderModelError_VRRenderModelError_None
+    };
+    required
+}
+
+unsafe extern "C" fn get_render_model_count() -> u32 {
+    SCRIPT.with(|script| script.borrow().render_model_names.len() as u32)
+}
+
+unsafe extern "C" fn get_render_model_name(index: u32, buffer: *mut c_char, len: u32) -> u32 {
+    SCRIPT.with(|script| {
+        match script.borrow().render_model_names.get(index as usize) {
+            Some(name) => write_string(name.as_bytes(), buffer, len),
+            None => 0
+        }
+    })
+}
+
+unsafe extern "C" fn get_component_count(model_name: *mut c_char) -> u32 {
+    let model_name = CStr::from_ptr(model_name).to_string_lossy();
+    SCRIPT.with(|script| {
+        script.borrow().components.get(&*model_name).map_or(0, |components| components.len() as u32)
+    })
+}
+
+unsafe extern "C" fn get_component_name(model_name: *mut c_char, index: u32, buffer: *mut c_char, len: u32) -> u32 {
+    let model_name = CStr::from_ptr(model_name).to_string_lossy();
+    SCRIPT.with(|script| {
+        let script = script.borrow();
+        match script.components.get(&*model_name).and_then(|components| components.get(index as usize)) {
+            Some(component) => write_string(&component.name, buffer, len),
+            None => 0
+        }
+    })
+}
+
+unsafe extern "C" fn get_component_render_model_name(model_name: *mut c_char, component_name: *mut c_char, buffer: *mut c_char, len: u32) -> u32 {
+    SCRIPT.with(|script| {
+        let script = script.borrow();
+        match script.component(model_name, component_name).and_then(|component| component.render_model_name.as_ref()) {
+            Some(name) => write_string(name.as_bytes(), buffer, len),
+            None => 0
+        }
+    })
+}
+
+unsafe extern "C" fn get_component_button_mask(model_name: *mut c_char, component_name: *mut c_char) -> u64 {
+    SCRIPT.with(|script| {
+        script.borrow().component(model_name, component_name).map_or(0, |component| component.button_mask)
+    })
+}
+
+unsafe extern "C" fn get_render_model_thumbnail_url(name: *mut c_char, buffer: *mut c_char, len: u32, err: *mut EVRRenderModelError) -> u32 {
+    SCRIPT.with(|script| {
+        let script = script.borrow();
+        let url = script.thumbnail_urls.get(&*CStr::from_ptr(name).to_string_lossy());
+        write_model_string(&script, name, url, buffer, len, err)
+    })
+}
+
+unsafe extern "C" fn get_render_model_original_path(name: *mut c_char, buffer: *mut c_char, len: u32, err: *mut EVRRenderModelError) -> u32 {
+    SCRIPT.with(|script| {
+        let script = script.borrow();
+        let path = script.original_paths.get(&*CStr::from_ptr(name).to_string_lossy());
+        write_model_string(&script, name, path, buffer, len, err)
+    })
+}
+
+unsafe extern "C" fn load_render_model_async(name: *mut c_char, model: *mut *mut openvr_sys::RenderModel_t) -> EVRRenderModelError {
+    let name = CStr::from_ptr(name).to_string_lossy();
+    SCRIPT.with(|script| {
+        let mut script = script.borrow_mut();
+        script.load_calls += 1;
+
+        match script.loads.get_mut(&*name).map(|load| load.poll()) {
+            Some(Ok(data)) => {
+                *model = data.as_raw();
+                EVRRenderModelError_VRRenderModelError_None
+            },
+            Some(Err(err)) => err,
+            None => EVRRenderModelError_VRRenderModelError_InvalidModel
+        }
+    })
+}
+
+unsafe extern "C" fn load_texture_async(id: openvr_sys::TextureID_t, texture: *mut *mut openvr_sys::RenderModel_TextureMap_t) -> EVRRenderModelError {
+    SCRIPT.with(|script| {
+        match script.borrow_mut().textures.get_mut(&id).map(|load| load.poll()) {
+            Some(Ok(data)) => {
+                *texture = data.as_raw();
+                EVRRenderModelError_VRRenderModelError_None
+            },
+            Some(Err(err)) => err,
+            None => EVRRenderModelError_VRRenderModelError_InvalidTexture
+        }
+    })
+}
+
+unsafe extern "C" fn free_render_model(_: *mut openvr_sys::RenderModel_t) {
+    SCRIPT.with(|script| script.borrow_mut().freed_render_models += 1);
+}
+
+unsafe extern "C" fn free_texture(_: *mut openvr_sys::RenderModel_TextureMap_t) {
+    SCRIPT.with(|script| script.borrow_mut().freed_textures += 1);
+}
+
+/// Builds subsystems backed by scripted data instead of the openvr runtime
+///  creating a mock replaces the script of the current thread, dropping it clears the script
+pub struct MockRuntime {
+    render_models: Box<openvr_sys::VR_IVRRenderModels_FnTable>
+}
+
+impl MockRuntime {
+    pub fn new() -> Self {
+        SCRIPT.with(|script| *script.borrow_mut() = Script::default());
+
+        let mut table: openvr_sys::VR_IVRRenderModels_FnTable = unsafe { mem::zeroed() };
+        table.GetRenderModelCount = Some(get_render_model_count);
+        table.GetRenderModelName = Some(get_render_model_name);
+        table.GetComponentCount = Some(get_component_count);
+        table.GetComponentName = Some(get_component_name);
+        table.GetComponentRenderModelName = Some(get_component_render_model_name);
+        table.GetComponentButtonMask = Some(get_component_button_mask);
+        table.GetRenderModelThumbnailURL = Some(get_render_model_thumbnail_url);
+        table.GetRenderModelOriginalPath = Some(get_render_model_original_path);
+        table.LoadRenderModel_Async = Some(load_render_model_async);
+        table.LoadTexture_Async = Some(load_texture_async);
+        table.FreeRenderModel = Some(free_render_model);
+        table.FreeTexture = Some(free_texture);
+
+        let mock = MockRuntime { render_models: Box::new(table) };
+        CURRENT_RENDER_MODELS.with(|current| current.set(mock.render_models_ptr()));
+        mock
+    }
+
+    fn render_models_ptr(&self) -> *const () {
+        &*self.render_models as *const openvr_sys::VR_IVRRenderModels_FnTable as *const ()
+    }
+
+    /// Sets the render models reported by IVRRenderModels::get_count and get_name
+    pub fn render_model_names(self, names: &[&str]) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().render_model_names = names.iter().map(|name| String::from(*name)).collect();
+        });
+        self
+    }
+
+    /// Sets the thumbnail url of a render model listed in render_model_names
+    ///  listed models without an url report an empty one, other models are unknown
+    pub fn thumbnail_url(self, name: &str, url: &str) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().thumbnail_urls.insert(String::from(name), String::from(url));
+        });
+        self
+    }
+
+    /// Sets the original path of a render model listed in render_model_names
+    ///  listed models without a path report an empty one, other models are unknown
+    pub fn original_path(self, name: &str, path: &str) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().original_paths.insert(String::from(name), String::from(path));
+        });
+        self
+    }
+
+    /// Adds a component to a render model, render_model_name is None for components without geometry
+    pub fn component(self, model_name: &str, component_name: &str, render_model_name: Option<&str>, button_mask: u64) -> Self {
+        self.push_component(model_name, MockComponent {
+            name: component_name.as_bytes().to_vec(),
+            render_model_name: render_model_name.map(String::from),
+            button_mask
+        })
+    }
+
+    /// Adds a component whose name is not valid utf-8, so reading its name fails
+    pub fn unreadable_component(self, model_name: &str) -> Self {
+        self.push_component(model_name, MockComponent {
+            name: vec![0xff, 0xfe],
+            render_model_name: None,
+            button_mask: 0
+        })
+    }
+
+    fn push_component(self, model_name: &str, component: MockComponent) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().components.entry(String::from(model_name)).or_insert_with(Vec::new).push(component);
+        });
+        self
+    }
+
+    /// Makes loading the render model with the given name succeed with the model
+    pub fn render_model(self, name: &str, model: MockRenderModel) -> Self {
+        self.script_load(name, MockLoad::Ready(model))
+    }
+
+    /// Makes loading the render model with the given name report Loading for the given number of polls
+    ///  before it succeeds with the model
+    pub fn delayed_render_model(self, name: &str, polls: usize, model: MockRenderModel) -> Self {
+        self.script_load(name, MockLoad::Delayed(polls, model))
+    }
+
+    /// Makes loading the render model with the given name report Loading forever
+    pub fn loading_render_model(self, name: &str) -> Self {
+        self.script_load(name, MockLoad::Loading)
+    }
+
+    /// Makes loading the render model with the given name fail with the error
+    ///  loading a name that is not scripted fails with InvalidModel
+    pub fn failing_render_model(self, name: &str, err: EVRRenderModelError) -> Self {
+        self.script_load(name, MockLoad::Failed(err))
+    }
+
+    fn script_load(self, name: &str, load: MockLoad<MockRenderModel>) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().loads.insert(String::from(name), load);
+        });
+        self
+    }
+
+    /// Makes loading the texture with the given id succeed with the texture
+    ///  pair it with MockRenderModel::diffuse_texture to load it through RenderModel::load_texture
+    pub fn texture(self, id: i32, texture: MockTexture) -> Self {
+        self.script_texture(id, MockLoad::Ready(texture))
+    }
+
+    /// Makes loading the texture with the given id report Loading forever
+    pub fn loading_texture(self, id: i32) -> Self {
+        self.script_texture(id, MockLoad::Loading)
+    }
+
+    /// Makes loading the texture with the given id fail with the error
+    ///  loading an id that is not scripted fails with InvalidTexture
+    pub fn failing_texture(self, id: i32, err: EVRRenderModelError) -> Self {
+        self.script_texture(id, MockLoad::Failed(err))
+    }
+
+    fn script_texture(self, id: i32, load: MockLoad<MockTexture>) -> Self {
+        SCRIPT.with(|script| {
+            script.borrow_mut().textures.insert(id, load);
+        });
+        self
+    }
+
+    /// Returns how often LoadRenderModel_Async has been called
+    pub fn load_calls(&self) -> usize {
+        SCRIPT.with(|script| script.borrow().load_calls)
+    }
+
+    /// Returns how many render models have been freed through the mock
+    pub fn freed_render_models(&self) -> usize {
+        SCRIPT.with(|script| script.borrow().freed_render_models)
+    }
+
+    /// Returns how many textures have been freed through the mock
+    pub fn freed_textures(&self) -> usize {
+        SCRIPT.with(|script| script.borrow().freed_textures)
+    }
+
+    /// Returns a render models subsystem answering with the scripted data
+    pub fn render_models(&self) -> Subsystem<IVRRenderModels> {
+        unsafe {
+            Subsystem::new(IVRRenderModels::from_raw(self.render_models_ptr()))
+        }
+    }
+}
+
+impl Default for MockRuntime {
+    fn default() -> Self {
+        MockRuntime::new()
+    }
+}
+
+impl Drop for MockRuntime {
+    fn drop(&mut self) {
+        CURRENT_RENDER_MODELS.with(|current| current.set(ptr::null()));
+        SCRIPT.with(|script| *script.borrow_mut() = Script::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_names() {
+        let mock = MockRuntime::new().render_model_names(&["vr_controller_vive_1_5", "lh_basestation_vive"]);
+        let models = mock.render_models();
+
+        assert_eq!(models.get_count(), 2);
+        assert_eq!(models.get_name(0).unwrap(), "vr_controller_vive_1_5");
+        assert_eq!(models.get_name(1).unwrap(), "lh_basestation_vive");
+        assert_eq!(models.get_name(2).unwrap(), "");
+    }
+
+    #[test]
+    fn scripted_components() {
+        let mock = MockRuntime::new()
+            .component("controller", "trigger", Some("controller_trigger"), 1 << 33)
+            .component("controller", "tip", None, 0);
+        let models = mock.render_models();
+
+        assert_eq!(models.component_count("controller"), 2);
+        assert_eq!(models.component_count("unknown"), 0);
+        assert_eq!(models.component_name("controller", 0).unwrap(), "trigger");
+        assert_eq!(models.component_render_model_name("controller", "trigger"), Some(String::from("controller_trigger")));
+        assert_eq!(models.component_render_model_name("controller", "tip"), None);
+        assert_eq!(models.component_button_mask("controller", "trigger"), 1 << 33);
+    }
+
+    #[test]
+    fn dropping_the_mock_clears_the_script() {
+        let mock = MockRuntime::new().render_model_names(&["a"]);
+        assert!(current_render_models().is_some());
+        drop(mock);
+
+        assert!(current_render_models().is_none());
+        SCRIPT.with(|script| assert!(script.borrow().render_model_names.is_empty()));
+    }
+}
@@ -0,0 +1,563 @@
+use openvr_sys;
+use openvr_sys::EVROverlayError::*;
+
+use std::ffi::CString;
+
+use common::*;
+use tracking::*;
+use error::*;
+use event::*;
+use constants::*;
+
+/// Identifies an overlay created by IVROverlay::create_overlay
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OverlayHandle(pub u64);
+
+impl OverlayHandle {
+    pub fn from_raw(raw: openvr_sys::VROverlayHandle_t) -> Self {
+        OverlayHandle(raw)
+    }
+
+    pub fn to_raw(&self) -> openvr_sys::VROverlayHandle_t {
+        self.0
+    }
+
+    /// Returns false for OVERLAY_HANDLE_INVALID, which never refers to an overlay
+    pub fn is_valid(&self) -> bool {
+        self.0 != OVERLAY_HANDLE_INVALID
+    }
+}
+
+/// Describes how an overlay receives input
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverlayInputMethod {
+    /// the overlay ignores input
+    None,
+    /// the overlay receives mouse events from the controllers
+    Mouse,
+}
+
+impl OverlayInputMethod {
+    pub fn to_raw(&self) -> openvr_sys::VROverlayInputMethod {
+        use openvr_sys::VROverlayInputMethod::*;
+
+        match *self {
+            OverlayInputMethod::None => VROverlayInputMethod_VROverlayInputMethod_None,
+            OverlayInputMethod::Mouse => VROverlayInputMethod_VROverlayInputMethod_Mouse,
+        }
+    }
+}
+
+/// Where a ray hits an overlay, see IVROverlay::compute_intersection
+#[derive(Debug, Copy, Clone)]
+pub struct OverlayIntersection {
+    pub point: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    /// distance from the source of the ray
+    pub distance: f32,
+}
+
+impl OverlayIntersection {
+    pub fn from_raw(raw: &openvr_sys::VROverlayIntersectionResults_t) -> Self {
+        OverlayIntersection {
+            point: raw.vPoint.v,
+            normal: raw.vNormal.v,
+            uv: raw.vUVs.v,
+            distance: raw.fDistance,
+        }
+    }
+}
+
+/// Iterates over the pending events of an overlay, see IVROverlay::poll_events
+pub struct OverlayEventIterator<'a> {
+    overlay: &'a IVROverlay,
+    handle: OverlayHandle
+}
+
+impl<'a> Iterator for OverlayEventIterator<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        use std;
+
+        unsafe {
+            let overlay = * { self.overlay.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let mut event: openvr_sys::VREvent_t = std::mem::zeroed();
+
+            let found = overlay.PollNextOverlayEvent.unwrap()(
+                self.handle.to_raw(),
+                &mut event,
+                std::mem::size_of::<openvr_sys::VREvent_t>() as u32
+            );
+
+            if found > 0 {
+                Some(Event::from_raw(event))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// The overlay subsystem, used to show 2d content in vr
+pub struct IVROverlay(pub *const ());
+
+impl IVROverlay {
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        IVROverlay(ptr as *mut ())
+    }
+
+    /// Creates a new overlay
+    ///  the key has to be unique (between all applications), the name is shown to the user
+    pub fn create_overlay(&self, key: &str, name: &str) -> Result<OverlayHandle, Error<openvr_sys::EVROverlayError>> {
+        let ckey = CString::new(key).unwrap();
+        let cname = CString::new(name).unwrap();
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let mut handle: openvr_sys::VROverlayHandle_t = OVERLAY_HANDLE_INVALID;
+            let err = overlay.CreateOverlay.unwrap()(
+                ckey.as_ptr() as *mut i8,
+                cname.as_ptr() as *mut i8,
+                &mut handle
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(OverlayHandle::from_raw(handle))
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Creates a new dashboard overlay, shown as a tab in the dashboard
+    ///  returns the handle of the main overlay and of its thumbnail
+    pub fn create_dashboard_overlay(&self, key: &str, name: &str) -> Result<(OverlayHandle, OverlayHandle), Error<openvr_sys::EVROverlayError>> {
+        let ckey = CString::new(key).unwrap();
+        let cname = CString::new(name).unwrap();
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let mut handle: openvr_sys::VROverlayHandle_t = OVERLAY_HANDLE_INVALID;
+            let mut thumbnail: openvr_sys::VROverlayHandle_t = OVERLAY_HANDLE_INVALID;
+            let err = overlay.CreateDashboardOverlay.unwrap()(
+                ckey.as_ptr() as *mut i8,
+                cname.as_ptr() as *mut i8,
+                &mut handle,
+                &mut thumbnail
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok((OverlayHandle::from_raw(handle), OverlayHandle::from_raw(thumbnail)))
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Destroys an overlay, the handle must not be used afterwards
+    pub fn destroy_overlay(&self, handle: OverlayHandle) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.DestroyOverlay.unwrap()(handle.to_raw());
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Shows the overlay
+    pub fn show_overlay(&self, handle: OverlayHandle) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.ShowOverlay.unwrap()(handle.to_raw());
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Hides the overlay
+    pub fn hide_overlay(&self, handle: OverlayHandle) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.HideOverlay.unwrap()(handle.to_raw());
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns true when the overlay is shown
+    pub fn is_overlay_visible(&self, handle: OverlayHandle) -> bool {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            overlay.IsOverlayVisible.unwrap()(handle.to_raw()) > 0
+        }
+    }
+
+    /// Sets the width of the overlay in meters, the height follows from the aspect ratio of the texture
+    pub fn set_overlay_width_in_meters(&self, handle: OverlayHandle, width: f32) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.SetOverlayWidthInMeters.unwrap()(handle.to_raw(), width);
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Places the overlay in the given tracking space
+    ///  the 3x4 matrix is row major like TrackedDevicePose::to_device
+    pub fn set_overlay_transform_absolute(&self, handle: OverlayHandle, origin: TrackingUniverseOrigin, transform: [[f32; 4]; 3]) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        let mut mat = openvr_sys::HmdMatrix34_t { m: transform };
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.SetOverlayTransformAbsolute.unwrap()(
+                handle.to_raw(),
+                origin.to_raw(),
+                &mut mat
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Sets how the overlay receives input
+    pub fn set_input_method(&self, handle: OverlayHandle, method: OverlayInputMethod) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.SetOverlayInputMethod.unwrap()(handle.to_raw(), method.to_raw());
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Casts a ray against the overlay, source and direction are given in the origin tracking space
+    ///  None when the ray misses the overlay
+    pub fn compute_intersection(&self, handle: OverlayHandle, origin: TrackingUniverseOrigin, source: [f32; 3], direction: [f32; 3]) -> Option<OverlayIntersection> {
+        use std;
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let mut params: openvr_sys::VROverlayIntersectionParams_t = std::mem::zeroed();
+            params.vSource.v = source;
+            params.vDirection.v = direction;
+            params.eOrigin = origin.to_raw();
+            let mut results: openvr_sys::VROverlayIntersectionResults_t = std::mem::zeroed();
+
+            let hit = overlay.ComputeOverlayIntersection.unwrap()(
+                handle.to_raw(),
+                &mut params,
+                &mut results
+            );
+
+            if hit > 0 {
+                Some(OverlayIntersection::from_raw(&results))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns an iterator that pops all pending events of the overlay
+    ///  use Event::mouse_position to get the cursor of mouse events
+    pub fn poll_events(&self, handle: OverlayHandle) -> OverlayEventIterator {
+        OverlayEventIterator { overlay: self, handle: handle }
+    }
+
+    /// Sets a gpu texture as the content of the overlay
+    pub fn set_texture(&self, handle: OverlayHandle, texture: &Texture) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        let mut t = texture.to_raw();
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.SetOverlayTexture.unwrap()(handle.to_raw(), &mut t);
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+
+    /// Sets a cpu buffer as the content of the overlay, the data is copied
+    ///  the length of data has to be width * height * bytes_per_pixel,
+    ///  otherwise the VROverlayError_InvalidParameter error is returned
+    pub fn set_raw(&self, handle: OverlayHandle, data: &[u8], width: u32, height: u32, bytes_per_pixel: u32) -> Result<(), Error<openvr_sys::EVROverlayError>> {
+        let expected = width as usize * height as usize * bytes_per_pixel as usize;
+        if data.len() != expected {
+            return Err(Error::from_raw(EVROverlayError_VROverlayError_InvalidParameter));
+        }
+
+        unsafe {
+            let overlay = * { self.0 as *mut openvr_sys::VR_IVROverlay_FnTable };
+            let err = overlay.SetOverlayRaw.unwrap()(
+                handle.to_raw(),
+                data.as_ptr() as *mut _,
+                width,
+                height,
+                bytes_per_pixel
+            );
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::raw::c_char;
+    use stub;
+
+    fn overlay(table: &openvr_sys::VR_IVROverlay_FnTable) -> IVROverlay {
+        IVROverlay(stub::table_ptr(table))
+    }
+
+    #[test]
+    fn invalid_handle_is_not_valid() {
+        assert!(!OverlayHandle::from_raw(OVERLAY_HANDLE_INVALID).is_valid());
+        assert!(OverlayHandle::from_raw(1).is_valid());
+    }
+
+    #[test]
+    fn set_raw_rejects_buffers_of_the_wrong_size() {
+        // the size is checked before openvr is called, so no runtime is needed
+        let overlay = unsafe { IVROverlay::from_raw(::std::ptr::null()) };
+        let handle = OverlayHandle(1);
+
+        let too_short = vec![0u8; 2 * 2 * 4 - 1];
+        let err = overlay.set_raw(handle, &too_short, 2, 2, 4).unwrap_err();
+        assert_eq!(err.to_raw(), EVROverlayError_VROverlayError_InvalidParameter);
+
+        let too_long = vec![0u8; 2 * 2 * 3 + 1];
+        let err = overlay.set_raw(handle, &too_long, 2, 2, 3).unwrap_err();
+        assert_eq!(err.to_raw(), EVROverlayError_VROverlayError_InvalidParameter);
+    }
+
+    unsafe extern "C" fn create_overlay(key: *mut c_char, name: *mut c_char, handle: *mut openvr_sys::VROverlayHandle_t) -> openvr_sys::EVROverlayError {
+        let key = stub::read_str(key);
+        stub::record(format!("CreateOverlay {} {}", key, stub::read_str(name)));
+        if key == "taken" {
+            return EVROverlayError_VROverlayError_KeyInUse;
+        }
+
+        *handle = 17;
+        EVROverlayError_VROverlayError_None
+    }
+
+    unsafe extern "C" fn destroy_overlay(handle: openvr_sys::VROverlayHandle_t) -> openvr_sys::EVROverlayError {
+        stub::record(format!("DestroyOverlay {}", handle));
+        EVROverlayError_VROverlayError_None
+    }
+
+    #[test]
+    fn create_overlay_returns_the_handle() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVROverlay_FnTable>();
+        table.CreateOverlay = stub!(create_overlay);
+        table.DestroyOverlay = stub!(destroy_overlay);
+        let overlay = overlay(&table);
+
+        let handle = overlay.create_overlay("example.menu", "Menu").unwrap();
+        assert_eq!(handle, OverlayHandle(17));
+        assert!(handle.is_valid());
+        overlay.destroy_overlay(handle).unwrap();
+
+        let err = overlay.create_overlay("taken", "Other").unwrap_err();
+        assert_eq!(err.to_raw(), EVROverlayError_VROverlayError_KeyInUse);
+        assert_eq!(stub::take_calls(), vec![
+            "CreateOverlay example.menu Menu",
+            "DestroyOverlay 17",
+            "CreateOverlay taken Other",
+        ]);
+    }
+
+    thread_local! {
+        static EVENTS: ::std::cell::RefCell<Vec<openvr_sys::VREvent_t>> = ::std::cell::RefCell::new(Vec::new());
+    }
+
+    unsafe extern "C" fn poll_next_overlay_event(handle: openvr_sys::VROverlayHandle_t, event: *mut openvr_sys::VREvent_t, size: u32) -> stub::Bool {
+        assert_eq!(handle, 17);
+        assert_eq!(size as usize, ::std::mem::size_of::<openvr_sys::VREvent_t>());
+        match EVENTS.with(|events| events.borrow_mut().pop()) {
+            Some(next) => {
+                *event = next;
+                1
+            },
+            None => 0
+        }
+    }
+
+    #[test]
+    fn poll_events_drains_the_overlay_queue() {
+        EVENTS.with(|events| {
+            let mut events = events.borrow_mut();
+            let mut raw: openvr_sys::VREvent_t = unsafe { ::std::mem::zeroed() };
+
+            // popped from the back
+            raw.eventType = EventType::OverlayHidden.to_raw();
+            events.push(raw);
+            raw.eventType = EventType::MouseMove.to_raw();
+            unsafe {
+                let mouse = &mut *(&mut raw.data as *mut _ as *mut openvr_sys::VREvent_Mouse_t);
+                mouse.x = 0.25;
+                mouse.y = 0.75;
+            }
+            events.push(raw);
+        });
+
+        let mut table = stub::empty_table::<openvr_sys::VR_IVROverlay_FnTable>();
+        table.PollNextOverlayEvent = stub!(poll_next_overlay_event);
+        let overlay = overlay(&table);
+
+        let drained: Vec<(EventType, Option<(f32, f32)>)> = overlay.poll_events(OverlayHandle(17))
+            .map(|event| (event.event_type, event.mouse_position()))
+            .collect();
+        assert_eq!(drained, vec![(EventType::MouseMove, Some((0.25, 0.75))), (EventType::OverlayHidden, None)]);
+        assert_eq!(overlay.poll_events(OverlayHandle(17)).count(), 0);
+    }
+
+    thread_local! {
+        static VISIBLE: ::std::cell::Cell<bool> = ::std::cell::Cell::new(false);
+    }
+
+    unsafe extern "C" fn show_overlay(handle: openvr_sys::VROverlayHandle_t) -> openvr_sys::EVROverlayError {
+        if handle != 17 {
+            return EVROverlayError_VROverlayError_UnknownOverlay;
+        }
+        VISIBLE.with(|visible| visible.set(true));
+        EVROverlayError_VROverlayError_None
+    }
+
+    unsafe extern "C" fn hide_overlay(handle: openvr_sys::VROverlayHandle_t) -> openvr_sys::EVROverlayError {
+        if handle != 17 {
+            return EVROverlayError_VROverlayError_UnknownOverlay;
+        }
+        VISIBLE.with(|visible| visible.set(false));
+        EVROverlayError_VROverlayError_None
+    }
+
+    unsafe extern "C" fn is_overlay_visible(handle: openvr_sys::VROverlayHandle_t) -> stub::Bool {
+        (handle == 17 && VISIBLE.with(|visible| visible.get())) as stub::Bool
+    }
+
+    #[test]
+    fn show_and_hide_round_trip_the_visibility() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVROverlay_FnTable>();
+        table.ShowOverlay = stub!(show_overlay);
+        table.HideOverlay = stub!(hide_overlay);
+        table.IsOverlayVisible = stub!(is_overlay_visible);
+        let overlay = overlay(&table);
+        let handle = OverlayHandle(17);
+
+        assert!(!overlay.is_overlay_visible(handle));
+        overlay.show_overlay(handle).unwrap();
+        assert!(overlay.is_overlay_visible(handle));
+        overlay.hide_overlay(handle).unwrap();
+        assert!(!overlay.is_overlay_visible(handle));
+
+        let err = overlay.show_overlay(OverlayHandle(18)).unwrap_err();
+        assert_eq!(err.to_raw(), EVROverlayError_VROverlayError_UnknownOverlay);
+        assert!(!overlay.is_overlay_visible(OverlayHandle(18)));
+    }
+
+    unsafe extern "C" fn create_dashboard_overlay(key: *mut c_char, name: *mut c_char, handle: *mut openvr_sys::VROverlayHandle_t, thumbnail: *mut openvr_sys::VROverlayHandle_t) -> openvr_sys::EVROverlayError {
+        stub::record(format!("CreateDashboardOverlay {} {}", stub::read_str(key), stub::read_str(name)));
+        *handle = 21;
+        *thumbnail = 22;
+        EVROverlayError_VROverlayError_None
+    }
+
+    #[test]
+    fn create_dashboard_overlay_returns_both_handles() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVROverlay_FnTable>();
+        table.CreateDashboardOverlay = stub!(create_dashboard_overlay);
+
+        let handles = overlay(&table).create_dashboard_overlay("example.dashboard", "Example").unwrap();
+        assert_eq!(handles, (OverlayHandle(21), OverlayHandle(22)));
+        assert_eq!(stub::take_calls(), vec!["CreateDashboardOverlay example.dashboard Example"]);
+    }
+
+    unsafe extern "C" fn set_overlay_input_method(handle: openvr_sys::VROverlayHandle_t, method: openvr_sys::VROverlayInputMethod) -> openvr_sys::EVROverlayError {
+        stub::record(format!("SetOverlayInputMethod {} {}", handle, method == OverlayInputMethod::Mouse.to_raw()));
+        EVROverlayError_VROverlayError_None
+    }
+
+    // the overlay lies in the plane z = -1, rays pointing away from it miss
+    unsafe extern "C" fn compute_overlay_intersection(_: openvr_sys::VROverlayHandle_t, params: *mut openvr_sys::VROverlayIntersectionParams_t, results: *mut openvr_sys::VROverlayIntersectionResults_t) -> stub::Bool {
+        let params = &*params;
+        assert_eq!(TrackingUniverseOrigin::from_raw(params.eOrigin), TrackingUniverseOrigin::Standing);
+        let source = params.vSource.v;
+        let direction = params.vDirection.v;
+        if direction[2] >= 0.0 {
+            return 0;
+        }
+
+        let distance = (-1.0 - source[2]) / direction[2];
+        let results = &mut *results;
+        results.vPoint.v = [source[0] + direction[0] * distance, source[1] + direction[1] * distance, -1.0];
+        results.vNormal.v = [0.0, 0.0, 1.0];
+        results.vUVs.v = [0.5, 0.5];
+        results.fDistance = distance;
+        1
+    }
+
+    #[test]
+    fn compute_intersection_reports_hits_and_misses() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVROverlay_FnTable>();
+        table.SetOverlayInputMethod = stub!(set_overlay_input_method);
+        table.ComputeOverlayIntersection = stub!(compute_overlay_intersection);
+        let overlay = overlay(&table);
+        let handle = OverlayHandle(17);
+
+        overlay.set_input_method(handle, OverlayInputMethod::Mouse).unwrap();
+        overlay.set_input_method(handle, OverlayInputMethod::None).unwrap();
+        assert_eq!(stub::take_calls(), vec!["SetOverlayInputMethod 17 true", "SetOverlayInputMethod 17 false"]);
+
+        let hit = overlay.compute_intersection(handle, TrackingUniverseOrigin::Standing, [0.0, 1.0, 1.0], [0.0, 0.0, -1.0]).unwrap();
+        assert_eq!(hit.point, [0.0, 1.0, -1.0]);
+        assert_eq!(hit.normal, [0.0, 0.0, 1.0]);
+        assert_eq!(hit.uv, [0.5, 0.5]);
+        assert_eq!(hit.distance, 2.0);
+
+        assert!(overlay.compute_intersection(handle, TrackingUniverseOrigin::Standing, [0.0, 1.0, 1.0], [0.0, 0.0, 1.0]).is_none());
+    }
+}
@@ -1,43 +1,367 @@
 use openvr_sys;
 use openvr_sys::EVRRenderModelError::*;
+use openvr_sys::EVRComponentProperty::*;
 
 use std::string::String;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::ffi::CString;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
 use std::ptr::null_mut;
 use std::slice;
+use std::time::{Duration, Instant};
+#[cfg(not(test))]
 use subsystems::render_models;
+use tracking::ControllerState;
+use common::{read_sized_string, ReadStringError};
 use error::*;
+#[cfg(any(test, feature = "mock"))]
+use mock;
 
 pub struct IVRRenderModels(pub *const ());
 
+/// checks whether the render models subsystem can be used (initialization is required beforehand)
+///  a mock alive on the thread counts as available
+pub fn is_available() -> bool {
+    owning_render_models().is_some()
+}
+
+// the subsystem freeing dropped models and textures, a mock alive on the thread takes precedence
+#[cfg(not(test))]
+fn owning_render_models() -> Option<IVRRenderModels> {
+    #[cfg(feature = "mock")]
+    {
+        if let Some(models) = mock::current_render_models() {
+            return Some(models);
+        }
+    }
+
+    render_models().ok()
+}
+
+// the crate's tests never talk to a runtime, without a mock there is nothing to free
+#[cfg(test)]
+fn owning_render_models() -> Option<IVRRenderModels> {
+    mock::current_render_models()
+}
+
 pub struct RenderModel(*mut openvr_sys::RenderModel_t);
 pub struct RenderModelTexture(*mut openvr_sys::RenderModel_TextureMap_t);
 
-trait AsyncError {
-    /// checks if result is currently loading
-    fn is_loading(&self) -> bool;
+/// reads a string from openvr like read_string, for queries reporting errors through an out parameter
+///  an empty string is treated as an error, as openvr has nothing to report then
+fn read_string_checked<F>(mut fetch: F) -> Result<String, Error<openvr_sys::EVRRenderModelError>>
+    where F: FnMut(*mut i8, u32, &mut openvr_sys::EVRRenderModelError) -> u32
+{
+    let mut err = EVRRenderModelError_VRRenderModelError_None;
+    let result = read_string(|buffer, len| fetch(buffer, len, &mut err));
+
+    // only the error of the last call is relevant, the sizing call may report a too small buffer
+    let error = Error::from_raw(err);
+    if error.is_err() {
+        return Err(error);
+    }
+
+    match result {
+        Ok(string) => {
+            if string.is_empty() {
+                Err(Error::from_raw(EVRRenderModelError_VRRenderModelError_InvalidModel))
+            } else {
+                Ok(string)
+            }
+        },
+        Err(err) => {
+            Err(err)
+        }
+    }
+}
+
+/// Describes the pose and visibility of a render model component
+#[derive(Debug, Copy, Clone)]
+pub struct ComponentState {
+    /// transform from tracking space to the component render model
+    pub render_transform: [[f32; 4]; 3],
+    /// transform from tracking space to the local coordinate system of the component
+    pub base_transform: [[f32; 4]; 3],
+    /// raw EVRComponentProperty flags
+    pub properties: u32,
+}
+
+impl ComponentState {
+    pub fn from_raw(raw: openvr_sys::RenderModel_ComponentState_t) -> Self {
+        ComponentState {
+            render_transform: raw.mTrackingToComponentRenderModel.m,
+            base_transform: raw.mTrackingToComponentLocal.m,
+            properties: raw.uProperties,
+        }
+    }
+
+    fn has_property(&self, property: openvr_sys::EVRComponentProperty) -> bool {
+        self.properties & property as u32 != 0
+    }
+
+    /// the component never moves relative to the model
+    pub fn is_static(&self) -> bool {
+        self.has_property(EVRComponentProperty_VRComponentProperty_IsStatic)
+    }
+
+    /// the component should be rendered
+    pub fn is_visible(&self) -> bool {
+        self.has_property(EVRComponentProperty_VRComponentProperty_IsVisible)
+    }
+
+    pub fn is_touched(&self) -> bool {
+        self.has_property(EVRComponentProperty_VRComponentProperty_IsTouched)
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.has_property(EVRComponentProperty_VRComponentProperty_IsPressed)
+    }
+
+    pub fn is_scrolled(&self) -> bool {
+        self.has_property(EVRComponentProperty_VRComponentProperty_IsScrolled)
+    }
+}
+
+// The model and texture memory is a plain allocation owned by openvr, which is only
+//  released through FreeRenderModel/FreeTexture. Moving the wrappers to another thread
+//  is therefore fine, as long as the render models subsystem is still initialized
+//  when they are dropped (on whichever thread that happens).
+unsafe impl Send for RenderModel {}
+unsafe impl Send for RenderModelTexture {}
+
+/// Iterates over the names of all available render models
+pub struct RenderModelNameIterator<'a> {
+    models: &'a IVRRenderModels,
+    index: u32,
+    count: u32
+}
+
+impl<'a> Iterator for RenderModelNameIterator<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while self.index < self.count {
+            let name = self.models.get_name(self.index);
+            self.index += 1;
+
+            // skip names openvr couldn't provide
+            if let Ok(name) = name {
+                return Some(name);
+            }
+        }
+        None
+    }
+}
+
+/// Describes the pixel layout of a render model texture
+///  the bound openvr sdk only delivers rgba8 textures, newer sdks add more formats
+///  (like bc2 or bc4 compressed ones), so the enum may grow
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextureFormat {
+    /// 8 bit per channel red, green, blue, alpha
+    Rgba8
+}
+
+impl TextureFormat {
+    /// Returns the amount of bytes used per pixel
+    pub fn bytes_per_pixel(&self) -> usize {
+        match *self {
+            TextureFormat::Rgba8 => 4
+        }
+    }
+}
+
+/// A single vertex of a render model
+#[derive(Copy, Clone)]
+pub struct Vertex<'a>(&'a openvr_sys::RenderModel_Vertex_t);
+
+/// Iterates over the vertices of a render model
+pub struct VertexIterator<'a> {
+    inner: slice::Iter<'a, openvr_sys::RenderModel_Vertex_t>
+}
+
+/// Describes the outcome of polling an asynchronous load
+pub enum LoadState<T> {
+    /// the resource is loaded
+    Ready(T),
+    /// openvr is still loading the resource, poll again later
+    Pending,
+    /// loading failed
+    Failed(Error<openvr_sys::EVRRenderModelError>)
 }
 
-impl AsyncError for Error<openvr_sys::EVRRenderModelError> {
-    fn is_loading(&self) -> bool {
-        match self.to_raw() {
-            EVRRenderModelError_VRRenderModelError_Loading => {
-                true
+impl<T> LoadState<T> {
+    fn from_result(result: Result<T, Error<openvr_sys::EVRRenderModelError>>) -> Self {
+        match result {
+            Ok(value) => {
+                LoadState::Ready(value)
             },
-            _ => {
-                false
+            Err(err) => {
+                if err.is_loading() {
+                    LoadState::Pending
+                } else {
+                    LoadState::Failed(err)
+                }
             }
         }
     }
 }
 
+/// Error of a blocking load that can time out
+#[derive(Debug)]
+pub enum LoadError {
+    /// the timeout was reached while openvr was still loading
+    Timeout,
+    /// openvr reported an error
+    Sys(Error<openvr_sys::EVRRenderModelError>)
+}
+
+impl LoadError {
+    // loads without a timeout can only fail with an openvr error
+    fn into_sys(self) -> Error<openvr_sys::EVRRenderModelError> {
+        match self {
+            LoadError::Sys(err) => err,
+            LoadError::Timeout => unreachable!("load without timeout timed out")
+        }
+    }
+}
+
+impl From<Error<openvr_sys::EVRRenderModelError>> for LoadError {
+    fn from(err: Error<openvr_sys::EVRRenderModelError>) -> LoadError {
+        LoadError::Sys(err)
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Timeout => write!(f, "timed out while the render model was loading"),
+            LoadError::Sys(ref err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl ::std::error::Error for LoadError {}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+/// checks if a blocking load has been running longer than the optional timeout
+fn timed_out(start: Instant, timeout: Option<Duration>) -> bool {
+    match timeout {
+        Some(timeout) => start.elapsed() >= timeout,
+        None => false
+    }
+}
+
+/// reads a string from openvr using the two call sizing pattern, see read_sized_string
+///  a changed size is reported as BufferTooSmall, invalid utf-8 as InvalidModel
+fn read_string<F>(fetch: F) -> Result<String, Error<openvr_sys::EVRRenderModelError>>
+    where F: FnMut(*mut i8, u32) -> u32
+{
+    read_sized_string(None, fetch).map_err(|err| Error::from_raw(match err {
+        ReadStringError::SizeChanged | ReadStringError::TooLong => EVRRenderModelError_VRRenderModelError_BufferTooSmall,
+        ReadStringError::InvalidUtf8 => EVRRenderModelError_VRRenderModelError_InvalidModel
+    }))
+}
+
+impl<'a> Vertex<'a> {
+    /// Returns the position of the vertex
+    pub fn position(&self) -> [f32; 3] {
+        self.0.vPosition.v
+    }
+
+    /// Returns the normal of the vertex
+    pub fn normal(&self) -> [f32; 3] {
+        self.0.vNormal.v
+    }
+
+    /// Returns the texture coordinate of the vertex
+    pub fn tex_coord(&self) -> [f32; 2] {
+        self.0.rfTextureCoord
+    }
+
+    /// Returns the position of the vertex as point, requires the `nalgebra` feature
+    ///  components are kept as is, openvr uses a right handed coordinate system (+y up, -z forward)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let models = openvr::render_models().unwrap();
+    /// let model = models.load(String::from("vr_controller_vive_1_5")).unwrap();
+    ///
+    /// for vertex in model.vertices() {
+    ///     let point = vertex.position_point();
+    ///     let normal = vertex.normal_vector();
+    ///     println!("{} {} {} facing {:?}", point.x, point.y, point.z, normal);
+    /// }
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    pub fn position_point(&self) -> nalgebra::Point3<f32> {
+        let p = self.position();
+        nalgebra::Point3::new(p[0], p[1], p[2])
+    }
+
+    /// Returns the normal of the vertex as vector, requires the `nalgebra` feature
+    #[cfg(feature = "nalgebra")]
+    pub fn normal_vector(&self) -> nalgebra::Vector3<f32> {
+        let n = self.normal();
+        nalgebra::Vector3::new(n[0], n[1], n[2])
+    }
+
+    /// Returns the underlying openvr vertex
+    pub fn to_raw(&self) -> &'a openvr_sys::RenderModel_Vertex_t {
+        self.0
+    }
+}
+
+impl<'a> Iterator for VertexIterator<'a> {
+    type Item = Vertex<'a>;
+
+    fn next(&mut self) -> Option<Vertex<'a>> {
+        self.inner.next().map(Vertex)
+    }
+}
+
+impl fmt::Debug for RenderModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RenderModel {{ vertex_count: {}, triangle_count: {} }}", self.vertex_count(), self.triangle_count())
+    }
+}
+
+impl fmt::Debug for RenderModelTexture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dimension = self.dimension();
+        write!(f, "RenderModelTexture {{ width: {}, height: {} }}", dimension.0, dimension.1)
+    }
+}
+
 impl Drop for RenderModel {
     /// will inform openvr that the memory for the render model is no longer required
     fn drop (&mut self) {
-        unsafe {
-            let models = * { render_models().unwrap().0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
-            models.FreeRenderModel.unwrap()(
-                self.0
-            );
+        // after shutdown the subsystem is gone and there is nothing left to free
+        if let Some(models) = owning_render_models() {
+            unsafe {
+                let models = * { models.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+                models.FreeRenderModel.unwrap()(
+                    self.0
+                );
+            }
         }
     }
 }
@@ -45,37 +369,211 @@ impl Drop for RenderModel {
 impl Drop for RenderModelTexture {
     /// will inform openvr that the memory for the render model is no longer required
     fn drop (&mut self) {
-        unsafe {
-            let models = * { render_models().unwrap().0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
-            models.FreeTexture.unwrap()(
-                self.0
-            );
+        // after shutdown the subsystem is gone and there is nothing left to free
+        if let Some(models) = owning_render_models() {
+            unsafe {
+                let models = * { models.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+                models.FreeTexture.unwrap()(
+                    self.0
+                );
+            }
         }
     }
 }
 
 impl RenderModel {
-    /// Returns an iterator that iterates over vertices
-    pub fn vertex_iter(&self) -> slice::Iter<openvr_sys::RenderModel_Vertex_t> {
+    /// Wraps a render model loaded by openvr, the wrapper frees the model when dropped
+    pub unsafe fn from_raw(ptr: *mut openvr_sys::RenderModel_t) -> Self {
+        RenderModel(ptr)
+    }
+
+    /// Returns the underlying openvr pointer, the model keeps the ownership
+    pub unsafe fn as_raw(&self) -> *mut openvr_sys::RenderModel_t {
+        self.0
+    }
+
+    /// informs openvr that the memory for the render model is no longer required
+    ///  same as dropping the model, but makes the release explicit
+    pub fn free(self) {
+        drop(self);
+    }
+
+    /// Returns the amount of vertices of the model
+    pub fn vertex_count(&self) -> usize {
+        unsafe {
+            (*self.0).unVertexCount as usize
+        }
+    }
+
+    /// Returns the amount of triangles of the model, the index count is three times this
+    pub fn triangle_count(&self) -> usize {
+        unsafe {
+            (*self.0).unTriangleCount as usize
+        }
+    }
+
+    /// Returns the vertices as one contiguous slice, e.g. for uploading them to the gpu
+    pub fn vertex_slice(&self) -> &[openvr_sys::RenderModel_Vertex_t] {
+        unsafe {
+            slice::from_raw_parts((*self.0).rVertexData, self.vertex_count())
+        }
+    }
+
+    /// Returns the indices as one contiguous slice, three indices form a triangle
+    pub fn index_slice(&self) -> &[u16] {
         unsafe {
-            let slice = slice::from_raw_parts((*self.0).rVertexData, (*self.0).unVertexCount as usize);
-            slice.iter()
+            slice::from_raw_parts((*self.0).rIndexData, self.triangle_count() * 3)
         }
     }
 
+    /// Returns the indices in chunks of whole triangles, e.g. to upload very large models in batches
+    ///  the last chunk may contain less triangles, a tris_per_chunk of 0 yields no chunks
+    pub fn index_chunks(&self, tris_per_chunk: usize) -> slice::Chunks<u16> {
+        if tris_per_chunk == 0 {
+            // chunks panics for a size of 0
+            return self.index_slice()[..0].chunks(1);
+        }
+        self.index_slice().chunks(tris_per_chunk * 3)
+    }
+
+    /// Copies vertices and indices into the given buffers, replacing their content
+    ///  the buffers keep their capacity, so they can be reused across models without reallocating
+    pub fn copy_into(&self, vertices: &mut Vec<openvr_sys::RenderModel_Vertex_t>, indices: &mut Vec<u16>) {
+        vertices.clear();
+        vertices.extend_from_slice(self.vertex_slice());
+        indices.clear();
+        indices.extend_from_slice(self.index_slice());
+    }
+
+    /// Returns an iterator that iterates over vertices
+    pub fn vertex_iter(&self) -> slice::Iter<openvr_sys::RenderModel_Vertex_t> {
+        self.vertex_slice().iter()
+    }
+
+    /// Returns an iterator that iterates over vertices with typed accessors
+    pub fn vertices(&self) -> VertexIterator {
+        VertexIterator { inner: self.vertex_iter() }
+    }
+
     /// Returns an iterator that iterates over indices
     pub fn index_iter(&self) -> slice::Iter<u16> {
+        self.index_slice().iter()
+    }
+
+    /// Computes the axis aligned bounding box of the model as (min, max) corners
+    ///  an empty model has both corners at the origin
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let mut vertices = self.vertices();
+        let first = match vertices.next() {
+            Some(vertex) => vertex.position(),
+            None => return ([0.0; 3], [0.0; 3])
+        };
+
+        let mut min = first;
+        let mut max = first;
+        for vertex in vertices {
+            let position = vertex.position();
+            for i in 0..3 {
+                min[i] = min[i].min(position[i]);
+                max[i] = max[i].max(position[i]);
+            }
+        }
+        (min, max)
+    }
+
+    /// Computes a tangent per vertex from positions, normals and texture coordinates, e.g. for normal mapping
+    ///  uses the per triangle accumulation by Eric Lengyel, w holds the handedness (1 or -1) of the bitangent
+    ///  vertices without usable texture coordinates get the tangent (1, 0, 0, 1)
+    pub fn compute_tangents(&self) -> Vec<[f32; 4]> {
+        let vertices = self.vertex_slice();
+        let mut tangents = vec![[0.0f32; 3]; vertices.len()];
+        let mut bitangents = vec![[0.0f32; 3]; vertices.len()];
+
+        for triangle in self.index_slice().chunks(3) {
+            let (i0, i1, i2) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+            let (v0, v1, v2) = (&vertices[i0], &vertices[i1], &vertices[i2]);
+
+            let e1 = sub(v1.vPosition.v, v0.vPosition.v);
+            let e2 = sub(v2.vPosition.v, v0.vPosition.v);
+            let du1 = v1.rfTextureCoord[0] - v0.rfTextureCoord[0];
+            let dv1 = v1.rfTextureCoord[1] - v0.rfTextureCoord[1];
+            let du2 = v2.rfTextureCoord[0] - v0.rfTextureCoord[0];
+            let dv2 = v2.rfTextureCoord[1] - v0.rfTextureCoord[1];
+
+            // skip triangles whose texture coordinates are degenerate, relative to their magnitude
+            let det = du1 * dv2 - du2 * dv1;
+            if det.abs() <= f32::EPSILON * (du1 * dv2).abs().max((du2 * dv1).abs()) {
+                continue;
+            }
+            let r = 1.0 / det;
+
+            let mut sdir = [0.0f32; 3];
+            let mut tdir = [0.0f32; 3];
+            for k in 0..3 {
+                sdir[k] = (e1[k] * dv2 - e2[k] * dv1) * r;
+                tdir[k] = (e2[k] * du1 - e1[k] * du2) * r;
+            }
+
+            for &i in &[i0, i1, i2] {
+                for k in 0..3 {
+                    tangents[i][k] += sdir[k];
+                    bitangents[i][k] += tdir[k];
+                }
+            }
+        }
+
+        vertices.iter().enumerate().map(|(i, vertex)| {
+            let n = vertex.vNormal.v;
+            let t = tangents[i];
+
+            // gram-schmidt orthogonalize against the normal
+            let d = dot(n, t);
+            let ortho = [t[0] - n[0] * d, t[1] - n[1] * d, t[2] - n[2] * d];
+            let length = dot(ortho, ortho).sqrt();
+            if length <= f32::EPSILON * dot(t, t).sqrt() {
+                return [1.0, 0.0, 0.0, 1.0];
+            }
+
+            let w = if dot(cross(n, t), bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+            [ortho[0] / length, ortho[1] / length, ortho[2] / length, w]
+        }).collect()
+    }
+
+    /// Writes the model in the wavefront obj format, e.g. to inspect it in a modelling tool
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for vertex in self.vertices() {
+            let position = vertex.position();
+            writeln!(writer, "v {} {} {}", position[0], position[1], position[2])?;
+        }
+        for vertex in self.vertices() {
+            let normal = vertex.normal();
+            writeln!(writer, "vn {} {} {}", normal[0], normal[1], normal[2])?;
+        }
+        for vertex in self.vertices() {
+            let tex_coord = vertex.tex_coord();
+            writeln!(writer, "vt {} {}", tex_coord[0], tex_coord[1])?;
+        }
+        // obj indices are one based
+        for triangle in self.index_slice().chunks(3) {
+            let (a, b, c) = (triangle[0] as u32 + 1, triangle[1] as u32 + 1, triangle[2] as u32 + 1);
+            writeln!(writer, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", a, b, c)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the id of the diffuse texture, models sharing a texture share the id
+    pub fn diffuse_texture_id(&self) -> i32 {
         unsafe {
-            let slice = slice::from_raw_parts((*self.0).rIndexData, (*self.0).unTriangleCount as usize * 3);
-            slice.iter()
+            (*self.0).diffuseTextureId
         }
     }
 
     /// asynchronosly loads the texture for the current render model
     /// see IVRRenderModels::load_async for info how openvr async work
+    ///  the texture is loaded through the subsystem that frees it, panics when there is none
     pub fn load_texture_async(&self) -> Result<RenderModelTexture, Error<openvr_sys::EVRRenderModelError>> {
         unsafe {
-            let models = * { render_models().unwrap().0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let models = * { owning_render_models().unwrap().0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
             let mut resp: *mut openvr_sys::RenderModel_TextureMap_t = null_mut();
 
             let err = models.LoadTexture_Async.unwrap()(
@@ -97,26 +595,61 @@ impl RenderModel {
 
     /// loads the texture for current model
     pub fn load_texture(&self) -> Result<RenderModelTexture, Error<openvr_sys::EVRRenderModelError>> {
+        self.load_texture_until(None).map_err(LoadError::into_sys)
+    }
+
+    /// loads the texture for current model, but gives up after the given timeout
+    ///  when the timeout is reached LoadError::Timeout is returned
+    pub fn load_texture_timeout(&self, timeout: Duration) -> Result<RenderModelTexture, LoadError> {
+        self.load_texture_until(Some(timeout))
+    }
+
+    /// polls the texture for the current model once without blocking
+    ///  designed to be called once per frame from within the render loop
+    pub fn try_load_texture(&self) -> LoadState<RenderModelTexture> {
+        LoadState::from_result(self.load_texture_async())
+    }
+
+    fn load_texture_until(&self, timeout: Option<Duration>) -> Result<RenderModelTexture, LoadError> {
         use std;
 
+        let start = Instant::now();
         loop {
-            let result = self.load_texture_async();
-            match result {
-                Ok(texture) => {
+            match self.try_load_texture() {
+                LoadState::Ready(texture) => {
                     return Ok(texture);
                 },
-                Err(err) => {
-                    if !err.is_loading() {
-                        return Err(err);
+                LoadState::Failed(err) => {
+                    return Err(LoadError::Sys(err));
+                },
+                LoadState::Pending => {
+                    if timed_out(start, timeout) {
+                        return Err(LoadError::Timeout);
                     }
                 }
             }
-            std::thread::sleep(std::time::Duration::from_millis(10));
+            std::thread::sleep(Duration::from_millis(10));
         }
     }
 }
 
 impl RenderModelTexture {
+    /// Wraps a texture loaded by openvr, the wrapper frees the texture when dropped
+    pub unsafe fn from_raw(ptr: *mut openvr_sys::RenderModel_TextureMap_t) -> Self {
+        RenderModelTexture(ptr)
+    }
+
+    /// Returns the underlying openvr pointer, the texture keeps the ownership
+    pub unsafe fn as_raw(&self) -> *mut openvr_sys::RenderModel_TextureMap_t {
+        self.0
+    }
+
+    /// informs openvr that the memory for the texture is no longer required
+    ///  same as dropping the texture, but makes the release explicit
+    pub fn free(self) {
+        drop(self);
+    }
+
     /// Returns the dimension from the texture (width, height)
     pub fn dimension(&self) -> (usize, usize) {
         unsafe {
@@ -124,16 +657,42 @@ impl RenderModelTexture {
         }
     }
 
-    /// Creates a 1 dimensional vector of pixels, format: rgba@32
+    /// Returns the pixel format of the texture
+    ///  the texture map of the current openvr sdk carries no format, it is always rgba8
+    pub fn format(&self) -> TextureFormat {
+        TextureFormat::Rgba8
+    }
+
+    /// Creates a 1 dimensional vector of pixels, see format() for the layout
+    ///  the pixels are copied as openvr delivers them, no conversion takes place
     pub fn to_vec(&self) -> Vec<u8> {
         unsafe {
             let dimension = self.dimension();
-            let slice = slice::from_raw_parts((*self.0).rubTextureMapData, dimension.0 * dimension.1 * 4);
+            let size = dimension.0 * dimension.1 * self.format().bytes_per_pixel();
+            let slice = slice::from_raw_parts((*self.0).rubTextureMapData, size);
             let mut vec = Vec::new();
             vec.extend_from_slice(slice);
             vec
         }
     }
+
+    /// Creates an rgba image from the texture, requires the `image` feature
+    #[cfg(feature = "image")]
+    pub fn to_image(&self) -> image::RgbaImage {
+        let dimension = self.dimension();
+        image::RgbaImage::from_raw(dimension.0 as u32, dimension.1 as u32, self.to_vec()).unwrap()
+    }
+
+    /// Writes the texture as png image, requires the `image` feature
+    #[cfg(feature = "image")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        use image::ImageEncoder;
+
+        let dimension = self.dimension();
+        image::codecs::png::PngEncoder::new(writer)
+            .write_image(&self.to_vec(), dimension.0 as u32, dimension.1 as u32, image::ColorType::Rgba8)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+    }
 }
 
 impl IVRRenderModels {
@@ -151,63 +710,226 @@ impl IVRRenderModels {
     }
 
     /// Returns the name of an available render model
-    pub fn get_name(&self, index: u32) -> String {
-        use std::ffi::CString;
+    pub fn get_name(&self, index: u32) -> Result<String, Error<openvr_sys::EVRRenderModelError>> {
         unsafe {
             let models = *{ self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable };
             let get_name_function = models.GetRenderModelName.unwrap();
-            let mut empty = vec![0i8;0];
-            let required = get_name_function(index, empty.as_mut_ptr(), 0);
-            if required == 0 {
-                return String::from("");
-            }
-            let mut name: Vec<u8> = Vec::with_capacity(required as usize);
-            let size = get_name_function(index, name.as_mut_ptr() as *mut i8, required);
-            if size != required {
-                panic!("name size changed");
-            }
-            let size_without_terminator = size - 1;
-            name.set_len(size_without_terminator as usize);
-            if let Ok(string) = CString::from_vec_unchecked(name).into_string() {
-                return string;
+            read_string(|buffer, len| get_name_function(index, buffer, len))
+        }
+    }
+
+    /// Returns an iterator over the names of all available render models
+    ///  names that can't be read are skipped
+    pub fn iter_names(&self) -> RenderModelNameIterator {
+        RenderModelNameIterator { models: self, index: 0, count: self.get_count() }
+    }
+
+    /// Returns the amount of components of a render model, e.g. trigger or trackpad of a controller
+    pub fn component_count(&self, render_model_name: &str) -> u32 {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cname = CString::new(render_model_name).unwrap();
+
+            models.GetComponentCount.unwrap()(cname.as_ptr() as *mut i8)
+        }
+    }
+
+    /// Returns the name of a component of a render model
+    pub fn component_name(&self, render_model_name: &str, index: u32) -> Result<String, Error<openvr_sys::EVRRenderModelError>> {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cname = CString::new(render_model_name).unwrap();
+            let get_component_name = models.GetComponentName.unwrap();
+
+            read_string(|buffer, len| get_component_name(cname.as_ptr() as *mut i8, index, buffer, len))
+        }
+    }
+
+    /// Returns the url of the thumbnail image of a render model
+    pub fn thumbnail_url(&self, model_name: &str) -> Result<String, Error<openvr_sys::EVRRenderModelError>> {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cname = CString::new(model_name).unwrap();
+            let get_thumbnail_url = models.GetRenderModelThumbnailURL.unwrap();
+
+            read_string_checked(|buffer, len, err| get_thumbnail_url(cname.as_ptr() as *mut i8, buffer, len, err))
+        }
+    }
+
+    /// Returns the path of the source files the render model was loaded from
+    pub fn original_path(&self, model_name: &str) -> Result<PathBuf, Error<openvr_sys::EVRRenderModelError>> {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cname = CString::new(model_name).unwrap();
+            let get_original_path = models.GetRenderModelOriginalPath.unwrap();
+
+            read_string_checked(|buffer, len, err| get_original_path(cname.as_ptr() as *mut i8, buffer, len, err))
+                .map(PathBuf::from)
+        }
+    }
+
+    /// Returns the name of the render model of a component, pass it to load to render the component
+    ///  None when the component has no render model (e.g. it only has a transform)
+    pub fn component_render_model_name(&self, model_name: &str, component_name: &str) -> Option<String> {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cmodel = CString::new(model_name).unwrap();
+            let ccomponent = CString::new(component_name).unwrap();
+            let get_render_model_name = models.GetComponentRenderModelName.unwrap();
+
+            let name = read_string(|buffer, len| get_render_model_name(
+                cmodel.as_ptr() as *mut i8,
+                ccomponent.as_ptr() as *mut i8,
+                buffer,
+                len
+            ));
+
+            match name {
+                Ok(name) => {
+                    if name.is_empty() { None } else { Some(name) }
+                },
+                Err(_) => {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns the buttons that affect a component, as bits like Button::mask
+    ///  0 when the component is not moved by any button
+    pub fn component_button_mask(&self, model_name: &str, component_name: &str) -> u64 {
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cmodel = CString::new(model_name).unwrap();
+            let ccomponent = CString::new(component_name).unwrap();
+
+            models.GetComponentButtonMask.unwrap()(
+                cmodel.as_ptr() as *mut i8,
+                ccomponent.as_ptr() as *mut i8
+            )
+        }
+    }
+
+    /// Loads the render models of all components of a controller (blocking)
+    ///  components without a render model are skipped, the timeout is shared by all loads
+    ///  components whose name can't be read or whose model fails to load are skipped as well,
+    ///  so one broken component doesn't discard the others, only reaching the timeout fails
+    ///  returns a list of component name and loaded render model
+    pub fn load_controller(&self, base_name: &str, timeout: Duration) -> Result<Vec<(String, RenderModel)>, LoadError> {
+        let start = Instant::now();
+        let mut components = Vec::new();
+
+        for index in 0..self.component_count(base_name) {
+            let component = match self.component_name(base_name, index) {
+                Ok(name) => name,
+                Err(_) => continue
+            };
+            let model_name = match self.component_render_model_name(base_name, &component) {
+                Some(name) => name,
+                None => continue
+            };
+
+            let remaining = timeout.checked_sub(start.elapsed()).unwrap_or(Duration::from_secs(0));
+            match self.load_until(&model_name, Some(remaining)) {
+                Ok(model) => {
+                    components.push((component, model));
+                },
+                Err(LoadError::Timeout) => {
+                    return Err(LoadError::Timeout);
+                },
+                Err(LoadError::Sys(_)) => {
+                    continue;
+                }
+            }
+        }
+
+        Ok(components)
+    }
+
+    /// Returns the pose of a render model component for the given controller state
+    ///  scroll tells openvr whether the scroll wheel of the controller is visible
+    ///  returns None when the component is unknown
+    pub fn component_state(&self, model_name: &str, component_name: &str, state: &ControllerState, scroll: bool) -> Option<ComponentState> {
+        use std;
+
+        unsafe {
+            let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
+            let cmodel = CString::new(model_name).unwrap();
+            let ccomponent = CString::new(component_name).unwrap();
+
+            let mut raw_state = state.to_raw();
+            let mut mode: openvr_sys::RenderModel_ControllerMode_State_t = std::mem::zeroed();
+            mode.bScrollWheelVisible = if scroll { 1 } else { 0 };
+            let mut result: openvr_sys::RenderModel_ComponentState_t = std::mem::zeroed();
+
+            let found = models.GetComponentState.unwrap()(
+                cmodel.as_ptr() as *mut i8,
+                ccomponent.as_ptr() as *mut i8,
+                &mut raw_state,
+                &mut mode,
+                &mut result
+            );
+
+            if found > 0 {
+                Some(ComponentState::from_raw(result))
             } else {
-                panic!("name cannot convert to utf8");
+                None
             }
-        };
+        }
     }
 
     /// Loads an render model into local memory
     ///  blocks the thread and waits until driver responds with model
     pub fn load(&self, name: String) -> Result<RenderModel, Error<openvr_sys::EVRRenderModelError>> {
+        self.load_until(&name, None).map_err(LoadError::into_sys)
+    }
+
+    /// Loads an render model into local memory
+    ///  blocks the thread until the driver responds with the model or the timeout is reached
+    ///  when the timeout is reached LoadError::Timeout is returned
+    pub fn load_timeout(&self, name: String, timeout: Duration) -> Result<RenderModel, LoadError> {
+        self.load_until(&name, Some(timeout))
+    }
+
+    fn load_until(&self, name: &str, timeout: Option<Duration>) -> Result<RenderModel, LoadError> {
         use std;
 
+        let start = Instant::now();
         loop {
-            let result = self.load_async(name.clone());
-            match result {
-                Ok(model) => {
+            match self.load_state(name) {
+                LoadState::Ready(model) => {
                     return Ok(model);
                 },
-                Err(err) => {
-                    if !err.is_loading() {
-                        return Err(err);
+                LoadState::Failed(err) => {
+                    return Err(LoadError::Sys(err));
+                },
+                LoadState::Pending => {
+                    if timed_out(start, timeout) {
+                        return Err(LoadError::Timeout);
                     }
                 }
             }
-            std::thread::sleep(std::time::Duration::from_millis(10));
+            std::thread::sleep(Duration::from_millis(10));
         }
     }
 
+    /// polls the render model once without blocking, see load_async
+    ///  separates a model that is still loading from a failed load
+    pub fn load_state(&self, name: &str) -> LoadState<RenderModel> {
+        LoadState::from_result(self.load_async(name))
+    }
+
     /// Loads an render model into local memory
     ///  When called for the first time openvr will start to load the model into memory
     ///  In the mean time this call will respond with EVRRenderModelError_VRRenderModelError_Loading
     ///  It is designed to be used wihtin the render loop as it won't block the user, for sync usage use load()
-    pub fn load_async(&self, name: String) -> Result<RenderModel, Error<openvr_sys::EVRRenderModelError>> {
+    pub fn load_async(&self, name: &str) -> Result<RenderModel, Error<openvr_sys::EVRRenderModelError>> {
         use std;
 
         unsafe {
             let models = * { self.0 as *mut openvr_sys::VR_IVRRenderModels_FnTable};
             let mut resp: *mut openvr_sys::RenderModel_t = null_mut();
-            let cname = std::ffi::CString::new(name.as_str()).unwrap();
+            let cname = CString::new(name).unwrap();
             let rawname = cname.into_raw();
 
             let err = models.LoadRenderModel_Async.unwrap()(
@@ -215,7 +937,7 @@ impl IVRRenderModels {
                 &mut resp
             );
 
-            let _ = std::ffi::CString::from_raw(rawname);
+            let _ = CString::from_raw(rawname);
 
             match err {
                 EVRRenderModelError_VRRenderModelError_None => {
@@ -228,3 +950,632 @@ impl IVRRenderModels {
         }
     }
 }
+
+/// Caches loaded render models by name, so loading a model again won't trigger the openvr loader
+///  the cache owns the models and hands out shared handles, a model is freed once it is
+///  removed from the cache and no handle is left
+pub struct RenderModelCache {
+    models: RefCell<HashMap<String, Rc<RenderModel>>>
+}
+
+impl RenderModelCache {
+    pub fn new() -> Self {
+        RenderModelCache {
+            models: RefCell::new(HashMap::new())
+        }
+    }
+
+    /// Returns the cached render model or loads it (blocking) when it is requested the first time
+    pub fn get_or_load(&self, models: &IVRRenderModels, name: &str) -> Result<Rc<RenderModel>, Error<openvr_sys::EVRRenderModelError>> {
+        if let Some(model) = self.models.borrow().get(name) {
+            return Ok(model.clone());
+        }
+
+        let model = Rc::new(models.load(String::from(name))?);
+        self.models.borrow_mut().insert(String::from(name), model.clone());
+        Ok(model)
+    }
+
+    /// Removes all render models from the cache
+    pub fn clear(&self) {
+        self.models.borrow_mut().clear();
+    }
+}
+
+impl Default for RenderModelCache {
+    fn default() -> Self {
+        RenderModelCache::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{MockRenderModel, MockRuntime, MockTexture};
+    use std::ptr;
+    use std::thread;
+
+    #[test]
+    fn get_name_sizes_the_buffer_to_the_name() {
+        let long_name = "a".repeat(300);
+        let mock = MockRuntime::new().render_model_names(&["short", &long_name]);
+        let models = mock.render_models();
+
+        assert_eq!(models.get_name(0).unwrap(), "short");
+        assert_eq!(models.get_name(1).unwrap(), long_name);
+    }
+
+    // answers like openvr: the sizing call gets the required size, the second call the string
+    fn fake_fetch(value: &'static [u8]) -> impl FnMut(*mut i8, u32) -> u32 {
+        move |buffer, len| {
+            let required = value.len() as u32 + 1;
+            if !buffer.is_null() && len >= required {
+                unsafe {
+                    ptr::copy_nonoverlapping(value.as_ptr(), buffer as *mut u8, value.len());
+                    *buffer.add(value.len()) = 0;
+                }
+            }
+            required
+        }
+    }
+
+    #[test]
+    fn read_string_sizes_and_strips_the_terminator() {
+        let mut calls = Vec::new();
+        let mut fetch = fake_fetch(b"lh_basestation_vive");
+        let name = read_string(|buffer, len| {
+            calls.push((buffer.is_null(), len));
+            fetch(buffer, len)
+        }).unwrap();
+
+        assert_eq!(name, "lh_basestation_vive");
+        assert_eq!(calls, vec![(true, 0), (false, 20)]);
+    }
+
+    #[test]
+    fn read_string_of_nothing_is_empty() {
+        let mut calls = 0;
+        let name = read_string(|_, _| { calls += 1; 0 }).unwrap();
+
+        assert_eq!(name, "");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn read_string_reports_a_changed_size() {
+        let mut required = 5;
+        let err = read_string(|_, _| { required += 1; required }).unwrap_err();
+
+        assert_eq!(err.to_raw(), EVRRenderModelError_VRRenderModelError_BufferTooSmall);
+    }
+
+    #[test]
+    fn read_string_rejects_invalid_utf8() {
+        let err = read_string(fake_fetch(&[0xff, 0xfe, 0xfd])).unwrap_err();
+
+        assert_eq!(err.to_raw(), EVRRenderModelError_VRRenderModelError_InvalidModel);
+    }
+
+    #[test]
+    fn read_string_checked_treats_empty_as_invalid() {
+        let err = read_string_checked(|buffer, len, _| fake_fetch(b"")(buffer, len)).unwrap_err();
+
+        assert_eq!(err.to_raw(), EVRRenderModelError_VRRenderModelError_InvalidModel);
+    }
+
+    // 2x2 texture, every pixel has its own color, row by row
+    fn texture_2x2() -> MockTexture {
+        MockTexture::new(2, 2, vec![
+            255, 0, 0, 255,    0, 255, 0, 255,
+            0, 0, 255, 255,    255, 255, 255, 0])
+    }
+
+    #[test]
+    fn to_vec_copies_the_pixels_row_by_row() {
+        let data = texture_2x2();
+        let texture = unsafe { RenderModelTexture::from_raw(data.as_raw()) };
+
+        assert_eq!(texture.dimension(), (2, 2));
+        assert_eq!(texture.to_vec()[4..8], [0, 255, 0, 255]);
+        assert_eq!(texture.to_vec().len(), 16);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn to_image_keeps_the_pixel_order() {
+        let data = texture_2x2();
+        let texture = unsafe { RenderModelTexture::from_raw(data.as_raw()) };
+        let image = texture.to_image();
+
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 0]);
+    }
+
+    #[test]
+    fn load_timeout_gives_up_while_loading() {
+        let mock = MockRuntime::new().loading_render_model("slow");
+        let models = mock.render_models();
+
+        let start = Instant::now();
+        match models.load_timeout(String::from("slow"), Duration::from_millis(50)) {
+            Err(LoadError::Timeout) => {},
+            other => panic!("expected a timeout, got {:?}", other)
+        }
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(2));
+        assert!(mock.load_calls() > 1);
+    }
+
+    #[test]
+    fn load_timeout_reports_openvr_errors() {
+        let mock = MockRuntime::new().failing_render_model("broken", EVRRenderModelError_VRRenderModelError_NoShapes);
+        let models = mock.render_models();
+
+        match models.load_timeout(String::from("broken"), Duration::from_secs(1)) {
+            Err(LoadError::Sys(err)) => assert!(err.is_no_shapes()),
+            other => panic!("expected an openvr error, got {:?}", other)
+        }
+        assert_eq!(mock.load_calls(), 1);
+    }
+
+    // unit quad in the xy plane facing +z, the texture coordinates follow x and y
+    fn quad() -> MockRenderModel {
+        MockRenderModel::new(vec![
+            MockRenderModel::vertex([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+            MockRenderModel::vertex([1.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 1.0]),
+            MockRenderModel::vertex([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0])
+        ], vec![0, 1, 2, 0, 2, 3])
+    }
+
+    #[test]
+    fn models_and_textures_move_to_other_threads() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RenderModel>();
+        assert_send::<RenderModelTexture>();
+
+        let data = quad();
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let counts = thread::spawn(move || (model.vertex_count(), model.triangle_count())).join().unwrap();
+
+        assert_eq!(counts, (4, 2));
+    }
+
+    #[test]
+    fn free_releases_exactly_once() {
+        let mock = MockRuntime::new();
+        let model_data = quad();
+        let texture_data = texture_2x2();
+
+        unsafe { RenderModel::from_raw(model_data.as_raw()) }.free();
+        unsafe { RenderModelTexture::from_raw(texture_data.as_raw()) }.free();
+        assert_eq!(mock.freed_render_models(), 1);
+        assert_eq!(mock.freed_textures(), 1);
+
+        drop(unsafe { RenderModel::from_raw(model_data.as_raw()) });
+        drop(unsafe { RenderModelTexture::from_raw(texture_data.as_raw()) });
+        assert_eq!(mock.freed_render_models(), 2);
+        assert_eq!(mock.freed_textures(), 2);
+    }
+
+    #[test]
+    fn drop_without_subsystem_does_not_panic() {
+        // no mock is alive on this thread, so there is no subsystem to free through
+        let model_data = quad();
+        let texture_data = texture_2x2();
+
+        drop(unsafe { RenderModel::from_raw(model_data.as_raw()) });
+        drop(unsafe { RenderModelTexture::from_raw(texture_data.as_raw()) });
+    }
+
+    #[test]
+    fn bounds_span_all_vertices() {
+        let data = MockRenderModel::new(vec![
+            MockRenderModel::vertex([0.5, -1.0, 2.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([-0.5, 3.0, 1.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([0.0, 0.0, -4.0], [0.0, 0.0, 1.0], [0.0, 0.0])
+        ], vec![0, 1, 2]);
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        assert_eq!(model.bounds(), ([-0.5, -1.0, -4.0], [0.5, 3.0, 2.0]));
+    }
+
+    #[test]
+    fn bounds_of_an_empty_model_are_zero() {
+        let data = MockRenderModel::new(Vec::new(), Vec::new());
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        assert_eq!(model.bounds(), ([0.0; 3], [0.0; 3]));
+    }
+
+    #[test]
+    fn write_obj_writes_one_based_faces() {
+        let data = quad();
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let mut obj = Vec::new();
+        model.write_obj(&mut obj).unwrap();
+
+        assert_eq!(String::from_utf8(obj).unwrap(), "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+vn 0 0 1
+vn 0 0 1
+vn 0 0 1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+f 1/1/1 2/2/2 3/3/3
+f 1/1/1 3/3/3 4/4/4
+");
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn write_png_writes_a_png() {
+        let data = texture_2x2();
+        let texture = unsafe { RenderModelTexture::from_raw(data.as_raw()) };
+        let mut png = Vec::new();
+        texture.write_png(&mut png).unwrap();
+
+        assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+    }
+
+    #[test]
+    fn cache_loads_each_model_once() {
+        let mock = MockRuntime::new().render_model("controller", quad());
+        let models = mock.render_models();
+        let cache = RenderModelCache::new();
+
+        let first = cache.get_or_load(&models, "controller").unwrap();
+        let second = cache.get_or_load(&models, "controller").unwrap();
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(mock.load_calls(), 1);
+        assert!(cache.get_or_load(&models, "unknown").unwrap_err().is_invalid_model());
+
+        // the model is freed once the cache and all handles let go of it
+        cache.clear();
+        drop(first);
+        assert_eq!(mock.freed_render_models(), 0);
+        drop(second);
+        assert_eq!(mock.freed_render_models(), 1);
+
+        cache.get_or_load(&models, "controller").unwrap();
+        assert_eq!(mock.load_calls(), 3);
+    }
+
+    #[test]
+    fn textures_are_rgba8() {
+        let data = texture_2x2();
+        let texture = unsafe { RenderModelTexture::from_raw(data.as_raw()) };
+
+        assert_eq!(texture.format(), TextureFormat::Rgba8);
+        assert_eq!(texture.to_vec().len(), 2 * 2 * texture.format().bytes_per_pixel());
+    }
+
+    #[test]
+    fn tangents_follow_the_texture_u_axis() {
+        let data = quad();
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        for tangent in model.compute_tangents() {
+            assert_eq!(tangent, [1.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn tangents_of_mirrored_texture_coordinates_flip_handedness() {
+        let data = MockRenderModel::new(vec![
+            MockRenderModel::vertex([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+            MockRenderModel::vertex([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 1.0])
+        ], vec![0, 1, 2]);
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        for tangent in model.compute_tangents() {
+            assert_eq!(tangent, [-1.0, 0.0, 0.0, -1.0]);
+        }
+    }
+
+    #[test]
+    fn tangents_fall_back_for_degenerate_texture_coordinates() {
+        let data = MockRenderModel::new(vec![
+            MockRenderModel::vertex([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.5, 0.5]),
+            MockRenderModel::vertex([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.5, 0.5]),
+            MockRenderModel::vertex([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.5, 0.5])
+        ], vec![0, 1, 2]);
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        assert_eq!(model.compute_tangents(), vec![[1.0, 0.0, 0.0, 1.0]; 3]);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_conversions_keep_the_component_order() {
+        let data = MockRenderModel::new(vec![
+            MockRenderModel::vertex([1.0, 2.0, 3.0], [0.0, 0.6, 0.8], [0.0, 0.0])
+        ], Vec::new());
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let vertex = model.vertices().next().unwrap();
+
+        assert_eq!(vertex.position_point(), nalgebra::Point3::new(1.0, 2.0, 3.0));
+        assert_eq!(vertex.normal_vector(), nalgebra::Vector3::new(0.0, 0.6, 0.8));
+    }
+
+    #[test]
+    fn load_controller_skips_broken_components() {
+        let mock = MockRuntime::new()
+            .component("controller", "body", Some("controller_body"), 0)
+            .component("controller", "tip", None, 0)
+            .unreadable_component("controller")
+            .component("controller", "button", Some("controller_missing"), 1 << 1)
+            .component("controller", "trigger", Some("controller_trigger"), 1 << 33)
+            .render_model("controller_body", quad())
+            .render_model("controller_trigger", quad());
+        let models = mock.render_models();
+
+        let components = models.load_controller("controller", Duration::from_secs(1)).unwrap();
+        let names: Vec<&str> = components.iter().map(|&(ref name, _)| &name[..]).collect();
+        assert_eq!(names, vec!["body", "trigger"]);
+    }
+
+    #[test]
+    fn load_controller_fails_on_timeout() {
+        let mock = MockRuntime::new()
+            .component("controller", "body", Some("controller_body"), 0)
+            .loading_render_model("controller_body");
+        let models = mock.render_models();
+
+        match models.load_controller("controller", Duration::from_millis(20)) {
+            Err(LoadError::Timeout) => {},
+            other => panic!("expected a timeout, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn diffuse_texture_id_reads_the_model() {
+        let untextured = quad();
+        assert_eq!(unsafe { RenderModel::from_raw(untextured.as_raw()) }.diffuse_texture_id(), -1);
+
+        let textured = quad().diffuse_texture(7);
+        let model = unsafe { RenderModel::from_raw(textured.as_raw()) };
+        assert_eq!(model.diffuse_texture_id(), 7);
+        assert_eq!(model.diffuse_texture_id(), unsafe { (*textured.as_raw()).diffuseTextureId });
+    }
+
+    #[test]
+    fn vertices_wrap_each_raw_vertex() {
+        let data = MockRenderModel::new(vec![
+            MockRenderModel::vertex([1.0, 2.0, 3.0], [0.0, 1.0, 0.0], [0.25, 0.75]),
+            MockRenderModel::vertex([-1.0, 0.5, 0.0], [0.0, 0.0, -1.0], [1.0, 0.0])
+        ], Vec::new());
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        let vertices: Vec<([f32; 3], [f32; 3], [f32; 2])> = model.vertices()
+            .map(|vertex| (vertex.position(), vertex.normal(), vertex.tex_coord()))
+            .collect();
+        assert_eq!(vertices, vec![
+            ([1.0, 2.0, 3.0], [0.0, 1.0, 0.0], [0.25, 0.75]),
+            ([-1.0, 0.5, 0.0], [0.0, 0.0, -1.0], [1.0, 0.0])
+        ]);
+        assert!(ptr::eq(model.vertices().nth(1).unwrap().to_raw(), &model.vertex_slice()[1]));
+    }
+
+    #[test]
+    fn slices_cover_the_raw_counts() {
+        let data = quad();
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+        let raw = unsafe { &*data.as_raw() };
+
+        assert_eq!(model.vertex_slice().len(), raw.unVertexCount as usize);
+        assert_eq!(model.index_slice().len(), raw.unTriangleCount as usize * 3);
+        assert_eq!(model.index_slice(), &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(model.vertex_iter().count(), 4);
+        assert_eq!(model.index_iter().count(), 6);
+    }
+
+    #[test]
+    fn component_state_decodes_the_property_flags() {
+        let mut raw: openvr_sys::RenderModel_ComponentState_t = unsafe { ::std::mem::zeroed() };
+        raw.mTrackingToComponentRenderModel.m = [[1.0, 0.0, 0.0, 0.5], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]];
+        raw.mTrackingToComponentLocal.m = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.25], [0.0, 0.0, 1.0, 0.0]];
+        raw.uProperties = EVRComponentProperty_VRComponentProperty_IsVisible as u32 | EVRComponentProperty_VRComponentProperty_IsPressed as u32;
+
+        let state = ComponentState::from_raw(raw);
+        assert_eq!(state.render_transform[0][3], 0.5);
+        assert_eq!(state.base_transform[1][3], 0.25);
+        assert!(state.is_visible() && state.is_pressed());
+        assert!(!state.is_static() && !state.is_touched() && !state.is_scrolled());
+
+        raw.uProperties = EVRComponentProperty_VRComponentProperty_IsStatic as u32
+            | EVRComponentProperty_VRComponentProperty_IsTouched as u32
+            | EVRComponentProperty_VRComponentProperty_IsScrolled as u32;
+        let state = ComponentState::from_raw(raw);
+        assert!(state.is_static() && state.is_touched() && state.is_scrolled());
+        assert!(!state.is_visible() && !state.is_pressed());
+    }
+
+    #[test]
+    fn thumbnail_url_sizes_the_buffer() {
+        let long_url = format!("file:///thumbnails/{}.png", "a".repeat(300));
+        let mock = MockRuntime::new()
+            .render_model_names(&["controller", "headset", "tracker"])
+            .thumbnail_url("controller", "file:///thumbnails/controller.png")
+            .thumbnail_url("headset", &long_url);
+        let models = mock.render_models();
+
+        assert_eq!(models.thumbnail_url("controller").unwrap(), "file:///thumbnails/controller.png");
+        assert_eq!(models.thumbnail_url("headset").unwrap(), long_url);
+
+        // a model without thumbnail reports an empty url
+        assert!(models.thumbnail_url("tracker").unwrap_err().is_invalid_model());
+        assert!(models.thumbnail_url("unknown").unwrap_err().is_invalid_model());
+    }
+
+    #[test]
+    fn original_path_reports_empty_paths_and_errors() {
+        let mock = MockRuntime::new()
+            .render_model_names(&["controller", "generated"])
+            .original_path("controller", "/steamvr/resources/rendermodels/controller/body.obj");
+        let models = mock.render_models();
+
+        assert_eq!(models.original_path("controller").unwrap(), PathBuf::from("/steamvr/resources/rendermodels/controller/body.obj"));
+        // zero length, the model has no source files
+        assert!(models.original_path("generated").unwrap_err().is_invalid_model());
+        // openvr reports the error through the out parameter
+        assert!(models.original_path("unknown").unwrap_err().is_invalid_model());
+    }
+
+    #[test]
+    fn try_load_texture_reports_all_outcomes() {
+        let mock = MockRuntime::new()
+            .texture(1, texture_2x2())
+            .loading_texture(2)
+            .failing_texture(3, EVRRenderModelError_VRRenderModelError_NotSupported);
+
+        let ready = quad().diffuse_texture(1);
+        match unsafe { RenderModel::from_raw(ready.as_raw()) }.try_load_texture() {
+            LoadState::Ready(texture) => assert_eq!(texture.dimension(), (2, 2)),
+            _ => panic!("expected the texture")
+        }
+
+        let pending = quad().diffuse_texture(2);
+        match unsafe { RenderModel::from_raw(pending.as_raw()) }.try_load_texture() {
+            LoadState::Pending => {},
+            _ => panic!("expected the texture to be loading")
+        }
+
+        let failing = quad().diffuse_texture(3);
+        match unsafe { RenderModel::from_raw(failing.as_raw()) }.try_load_texture() {
+            LoadState::Failed(err) => assert_eq!(err.to_raw(), EVRRenderModelError_VRRenderModelError_NotSupported),
+            _ => panic!("expected an openvr error")
+        }
+
+        // the blocking load is built on the same poll
+        let model = unsafe { RenderModel::from_raw(ready.as_raw()) };
+        assert_eq!(model.load_texture().unwrap().to_vec()[..4], [255, 0, 0, 255]);
+        assert_eq!(mock.freed_textures(), 2);
+    }
+
+    #[test]
+    fn iter_names_matches_a_manual_loop() {
+        let mock = MockRuntime::new().render_model_names(&["vr_controller_vive_1_5", "lh_basestation_vive", "generic_hmd"]);
+        let models = mock.render_models();
+
+        let mut manual = Vec::new();
+        for index in 0..models.get_count() {
+            manual.push(models.get_name(index).unwrap());
+        }
+        let iterated: Vec<String> = models.iter_names().collect();
+        assert_eq!(iterated, manual);
+        assert_eq!(iterated.len(), 3);
+
+        let vive: Vec<String> = models.iter_names().filter(|name| name.ends_with("_vive")).collect();
+        assert_eq!(vive, vec!["lh_basestation_vive"]);
+    }
+
+    #[test]
+    fn copy_into_reuses_the_buffers() {
+        let big = quad();
+        let small = MockRenderModel::new(vec![
+            MockRenderModel::vertex([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+            MockRenderModel::vertex([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+            MockRenderModel::vertex([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0])
+        ], vec![0, 1, 2]);
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        unsafe { RenderModel::from_raw(big.as_raw()) }.copy_into(&mut vertices, &mut indices);
+        assert_eq!((vertices.len(), indices.len()), (4, 6));
+        let capacities = (vertices.capacity(), indices.capacity());
+        let buffers = (vertices.as_ptr(), indices.as_ptr());
+
+        unsafe { RenderModel::from_raw(small.as_raw()) }.copy_into(&mut vertices, &mut indices);
+        assert_eq!((vertices.len(), indices.len()), (3, 3));
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!((vertices.capacity(), indices.capacity()), capacities);
+        assert_eq!((vertices.as_ptr(), indices.as_ptr()), buffers);
+    }
+
+    #[test]
+    fn debug_shows_counts_and_dimensions() {
+        let model_data = quad();
+        let texture_data = texture_2x2();
+        let model = unsafe { RenderModel::from_raw(model_data.as_raw()) };
+        let texture = unsafe { RenderModelTexture::from_raw(texture_data.as_raw()) };
+
+        assert_eq!(format!("{:?}", model), "RenderModel { vertex_count: 4, triangle_count: 2 }");
+        assert_eq!(format!("{:?}", texture), "RenderModelTexture { width: 2, height: 2 }");
+    }
+
+    #[test]
+    fn load_state_moves_from_pending_to_ready() {
+        let mock = MockRuntime::new()
+            .delayed_render_model("controller", 2, quad())
+            .failing_render_model("broken", EVRRenderModelError_VRRenderModelError_TooManyVertices);
+        let models = mock.render_models();
+
+        for _ in 0..2 {
+            match models.load_state("controller") {
+                LoadState::Pending => {},
+                _ => panic!("expected the model to be loading")
+            }
+        }
+        match models.load_state("controller") {
+            LoadState::Ready(model) => assert_eq!(model.triangle_count(), 2),
+            _ => panic!("expected the model")
+        }
+        match models.load_state("broken") {
+            LoadState::Failed(err) => assert_eq!(err.to_raw(), EVRRenderModelError_VRRenderModelError_TooManyVertices),
+            _ => panic!("expected an openvr error")
+        }
+        assert_eq!(mock.load_calls(), 4);
+    }
+
+    #[test]
+    fn available_while_a_mock_is_alive() {
+        assert!(!is_available());
+
+        let mock = MockRuntime::new();
+        assert!(is_available());
+        drop(mock);
+        assert!(!is_available());
+    }
+
+    #[test]
+    fn index_chunks_split_at_whole_triangles() {
+        let data = MockRenderModel::new(
+            (0..4).map(|i| MockRenderModel::vertex([i as f32, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0])).collect(),
+            vec![0, 1, 2, 1, 2, 3, 2, 3, 0, 3, 0, 1, 0, 2, 3]
+        );
+        let model = unsafe { RenderModel::from_raw(data.as_raw()) };
+
+        let lengths: Vec<usize> = model.index_chunks(2).map(|chunk| chunk.len()).collect();
+        assert_eq!(lengths, vec![6, 6, 3]);
+        assert!(lengths.iter().all(|length| length % 3 == 0));
+
+        let joined: Vec<u16> = model.index_chunks(2).flat_map(|chunk| chunk.iter().cloned()).collect();
+        assert_eq!(joined, model.index_slice());
+        assert_eq!(model.index_chunks(5).count(), 1);
+        assert_eq!(model.index_chunks(0).count(), 0);
+    }
+
+    #[test]
+    fn as_raw_returns_the_wrapped_pointer() {
+        let model_data = quad();
+        let texture_data = texture_2x2();
+        let model = unsafe { RenderModel::from_raw(model_data.as_raw()) };
+        let texture = unsafe { RenderModelTexture::from_raw(texture_data.as_raw()) };
+
+        assert_eq!(unsafe { model.as_raw() }, model_data.as_raw());
+        assert_eq!(unsafe { texture.as_raw() }, texture_data.as_raw());
+    }
+}
@@ -0,0 +1,245 @@
+use openvr_sys;
+use openvr_sys::EVRSettingsError::*;
+
+use std::ffi::CString;
+
+use error::*;
+
+// size of the buffer used to read string settings
+const MAX_STRING_SETTING_LENGTH: usize = 4096;
+
+/// The settings subsystem, reads and writes the settings of the runtime
+///  settings are identified by a section (like "steamvr") and a key inside the section
+///  openvr does not report missing keys as errors, the getters return the given default for them
+pub struct IVRSettings(pub *const ());
+
+impl IVRSettings {
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        IVRSettings(ptr as *mut ())
+    }
+
+    /// Calls an entry of the settings with the section and key as c strings, mapping the error out parameter
+    unsafe fn call<T, F>(&self, section: &str, key: &str, entry: F) -> Result<T, Error<openvr_sys::EVRSettingsError>>
+        where F: FnOnce(&openvr_sys::VR_IVRSettings_FnTable, *mut i8, *mut i8, *mut openvr_sys::EVRSettingsError) -> T
+    {
+        let csection = CString::new(section).unwrap();
+        let ckey = CString::new(key).unwrap();
+
+        let settings = &* { self.0 as *const openvr_sys::VR_IVRSettings_FnTable };
+        let mut err = EVRSettingsError_VRSettingsError_None;
+        let value = entry(
+            settings,
+            csection.as_ptr() as *mut i8,
+            ckey.as_ptr() as *mut i8,
+            &mut err
+        );
+
+        let error = Error::from_raw(err);
+        if error.is_ok() {
+            Ok(value)
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Gets a bool setting, default is returned when the key does not exist
+    pub fn get_bool(&self, section: &str, key: &str, default: bool) -> Result<bool, Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.GetBool.unwrap()(section, key, if default { 1 } else { 0 }, err) > 0
+            })
+        }
+    }
+
+    /// Sets a bool setting
+    pub fn set_bool(&self, section: &str, key: &str, value: bool) -> Result<(), Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.SetBool.unwrap()(section, key, if value { 1 } else { 0 }, err);
+            })
+        }
+    }
+
+    /// Gets an int32 setting, default is returned when the key does not exist
+    pub fn get_int32(&self, section: &str, key: &str, default: i32) -> Result<i32, Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.GetInt32.unwrap()(section, key, default, err)
+            })
+        }
+    }
+
+    /// Sets an int32 setting
+    pub fn set_int32(&self, section: &str, key: &str, value: i32) -> Result<(), Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.SetInt32.unwrap()(section, key, value, err);
+            })
+        }
+    }
+
+    /// Gets a float setting, default is returned when the key does not exist
+    pub fn get_float(&self, section: &str, key: &str, default: f32) -> Result<f32, Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.GetFloat.unwrap()(section, key, default, err)
+            })
+        }
+    }
+
+    /// Sets a float setting
+    pub fn set_float(&self, section: &str, key: &str, value: f32) -> Result<(), Error<openvr_sys::EVRSettingsError>> {
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.SetFloat.unwrap()(section, key, value, err);
+            })
+        }
+    }
+
+    /// Gets a string setting, default is returned when the key does not exist
+    ///  openvr cuts values off at the buffer size without reporting it, so values that fill the
+    ///  whole buffer (4095 bytes) return VRSettingsError_ReadFailed instead of a truncated string,
+    ///  the settings errors have no buffer size error; values that are no valid utf-8 fail the same way
+    pub fn get_string(&self, section: &str, key: &str, default: &str) -> Result<String, Error<openvr_sys::EVRSettingsError>> {
+        let cdefault = CString::new(default).unwrap();
+        let mut buffer: Vec<u8> = vec![0; MAX_STRING_SETTING_LENGTH];
+
+        unsafe {
+            let value = buffer.as_mut_ptr() as *mut i8;
+            self.call(section, key, |settings, section, key, err| {
+                settings.GetString.unwrap()(
+                    section,
+                    key,
+                    value,
+                    MAX_STRING_SETTING_LENGTH as u32,
+                    cdefault.as_ptr() as *mut i8,
+                    err
+                );
+            })?;
+        }
+
+        let length = buffer.iter().position(|b| *b == 0).unwrap_or(buffer.len());
+        if length + 1 >= MAX_STRING_SETTING_LENGTH {
+            return Err(Error::from_raw(EVRSettingsError_VRSettingsError_ReadFailed));
+        }
+
+        buffer.truncate(length);
+        String::from_utf8(buffer).map_err(|_| Error::from_raw(EVRSettingsError_VRSettingsError_ReadFailed))
+    }
+
+    /// Sets a string setting
+    pub fn set_string(&self, section: &str, key: &str, value: &str) -> Result<(), Error<openvr_sys::EVRSettingsError>> {
+        let cvalue = CString::new(value).unwrap();
+
+        unsafe {
+            self.call(section, key, |settings, section, key, err| {
+                settings.SetString.unwrap()(section, key, cvalue.as_ptr() as *mut i8, err);
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::raw::c_char;
+    use std::ptr;
+    use stub;
+
+    fn settings(table: &openvr_sys::VR_IVRSettings_FnTable) -> IVRSettings {
+        IVRSettings(stub::table_ptr(table))
+    }
+
+    // like openvr the stub reports no error for missing keys, a broken section fails to read
+    unsafe fn lookup(section: *mut c_char, key: *mut c_char, err: *mut openvr_sys::EVRSettingsError) -> Option<&'static str> {
+        let section = stub::read_str(section);
+        let key = stub::read_str(key);
+        stub::record(format!("{}.{}", section, key));
+
+        if section == "broken" {
+            *err = EVRSettingsError_VRSettingsError_ReadFailed;
+            return None;
+        }
+        *err = EVRSettingsError_VRSettingsError_None;
+        match (section.as_str(), key.as_str()) {
+            ("steamvr", "enableHomeApp") => Some("1"),
+            ("steamvr", "renderTargetMultiplier") => Some("1.5"),
+            ("steamvr", "language") => Some("en"),
+            ("steamvr", "long") => Some("<long>"),
+            _ => None
+        }
+    }
+
+    unsafe extern "C" fn get_bool(section: *mut c_char, key: *mut c_char, default: stub::Bool, err: *mut openvr_sys::EVRSettingsError) -> stub::Bool {
+        match lookup(section, key, err) {
+            Some(value) => (value == "1") as stub::Bool,
+            None => default
+        }
+    }
+
+    unsafe extern "C" fn get_float(section: *mut c_char, key: *mut c_char, default: f32, err: *mut openvr_sys::EVRSettingsError) -> f32 {
+        match lookup(section, key, err) {
+            Some(value) => value.parse().unwrap(),
+            None => default
+        }
+    }
+
+    unsafe extern "C" fn get_string(section: *mut c_char, key: *mut c_char, buffer: *mut c_char, len: u32, default: *mut c_char, err: *mut openvr_sys::EVRSettingsError) {
+        let value = match lookup(section, key, err) {
+            // stands for a value longer than the buffer
+            Some("<long>") => "x".repeat(MAX_STRING_SETTING_LENGTH),
+            Some(value) => value.to_string(),
+            None => stub::read_str(default)
+        };
+
+        // openvr cuts the value off at the buffer size
+        let length = value.len().min(len as usize - 1);
+        ptr::copy_nonoverlapping(value.as_ptr(), buffer as *mut u8, length);
+        *buffer.add(length) = 0;
+    }
+
+    fn table() -> openvr_sys::VR_IVRSettings_FnTable {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSettings_FnTable>();
+        table.GetBool = stub!(get_bool);
+        table.GetFloat = stub!(get_float);
+        table.GetString = stub!(get_string);
+        table
+    }
+
+    #[test]
+    fn missing_keys_return_the_default() {
+        let table = table();
+        let settings = settings(&table);
+
+        assert!(settings.get_bool("steamvr", "enableHomeApp", false).unwrap());
+        assert!(!settings.get_bool("steamvr", "missing", false).unwrap());
+        assert!(settings.get_bool("steamvr", "missing", true).unwrap());
+        assert_eq!(settings.get_float("steamvr", "renderTargetMultiplier", 1.0).unwrap(), 1.5);
+        assert_eq!(settings.get_float("steamvr", "missing", 2.0).unwrap(), 2.0);
+        assert_eq!(settings.get_string("steamvr", "language", "de").unwrap(), "en");
+        assert_eq!(settings.get_string("steamvr", "missing", "de").unwrap(), "de");
+        assert_eq!(stub::take_calls().len(), 7);
+    }
+
+    #[test]
+    fn read_errors_are_mapped() {
+        let table = table();
+        let settings = settings(&table);
+
+        let err = settings.get_bool("broken", "enableHomeApp", false).unwrap_err();
+        assert_eq!(err.to_raw(), EVRSettingsError_VRSettingsError_ReadFailed);
+        let err = settings.get_float("broken", "renderTargetMultiplier", 1.0).unwrap_err();
+        assert_eq!(err.to_raw(), EVRSettingsError_VRSettingsError_ReadFailed);
+        let err = settings.get_string("broken", "language", "").unwrap_err();
+        assert_eq!(err.to_raw(), EVRSettingsError_VRSettingsError_ReadFailed);
+        assert_eq!(stub::take_calls(), vec!["broken.enableHomeApp", "broken.renderTargetMultiplier", "broken.language"]);
+    }
+
+    #[test]
+    fn string_values_filling_the_buffer_are_not_truncated() {
+        let table = table();
+
+        let err = settings(&table).get_string("steamvr", "long", "").unwrap_err();
+        assert_eq!(err.to_raw(), EVRSettingsError_VRSettingsError_ReadFailed);
+    }
+}
@@ -0,0 +1,68 @@
+//! Helpers for tests running the subsystem wrappers against stub function tables
+//!  a test fills the entries it needs with stubs, every other entry stays None
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_char;
+use std::ptr;
+
+/// The c bool of the bindings, its size differs between the platforms (see tracked_camera)
+#[cfg(not(target_os = "windows"))]
+pub type Bool = i32;
+#[cfg(target_os = "windows")]
+pub type Bool = i8;
+
+/// Wraps a stub for a function table entry
+///  the bindings spell the c types of the entries differently between versions (bool, typedefs),
+///  so the stub is cast into the entry, its signature has to match the abi of the entry
+macro_rules! stub {
+    ($f:expr) => {
+        Some(unsafe { ::std::mem::transmute::<*const (), _>($f as *const ()) })
+    };
+}
+
+// the calls are recorded per thread, so parallel tests don't interfere
+thread_local! {
+    static CALLS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+/// Returns a function table with all entries set to None
+///  also clears the calls recorded on this thread, so each table starts a new recording
+pub fn empty_table<T>() -> T {
+    take_calls();
+    unsafe { mem::zeroed() }
+}
+
+/// Returns the pointer the subsystem wrappers expect for a function table
+pub fn table_ptr<T>(table: &T) -> *const () {
+    table as *const T as *const ()
+}
+
+/// Records the call of a stub
+pub fn record<S: Into<String>>(call: S) {
+    CALLS.with(|calls| calls.borrow_mut().push(call.into()));
+}
+
+/// Returns the calls recorded on this thread and clears them
+pub fn take_calls() -> Vec<String> {
+    CALLS.with(|calls| mem::replace(&mut *calls.borrow_mut(), Vec::new()))
+}
+
+/// Reads a string argument passed to a stub
+pub unsafe fn read_str(value: *const c_char) -> String {
+    CStr::from_ptr(value).to_string_lossy().into_owned()
+}
+
+/// Writes a string like openvr, returning the required size including the terminator
+///  nothing is written when the buffer is too small
+pub unsafe fn write_str(value: &str, buffer: *mut c_char, len: u32) -> u32 {
+    let required = value.len() as u32 + 1;
+    if buffer.is_null() || len < required {
+        return required;
+    }
+
+    ptr::copy_nonoverlapping(value.as_ptr(), buffer as *mut u8, value.len());
+    *buffer.add(value.len()) = 0;
+    required
+}
@@ -7,8 +7,39 @@ use extended_display::IVRExtendedDisplay;
 use compositor::IVRCompositor;
 use render_models::IVRRenderModels;
 use tracked_camera::IVRTrackedCamera;
+use chaperone::IVRChaperone;
+use chaperone_setup::IVRChaperoneSetup;
+use overlay::IVROverlay;
+use applications::IVRApplications;
+use settings::IVRSettings;
 
 use std;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// A subsystem interface borrowed from whatever keeps the runtime alive (like a Context)
+///  derefs to the interface, the borrow makes sure it is not used after the owner is gone
+pub struct Subsystem<'a, T> {
+    interface: T,
+    _owner: PhantomData<&'a ()>
+}
+
+impl<'a, T> Subsystem<'a, T> {
+    pub(crate) fn new(interface: T) -> Subsystem<'a, T> {
+        Subsystem {
+            interface,
+            _owner: PhantomData
+        }
+    }
+}
+
+impl<'a, T> Deref for Subsystem<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.interface
+    }
+}
 
 /// gets the current vr system interface (initialization is required beforehand)
 pub fn system() -> Result<IVRSystem, Error<openvr_sys::EVRInitError>> {
@@ -109,3 +140,103 @@ pub fn tracked_camera() -> Result<IVRTrackedCamera, Error<openvr_sys::EVRInitErr
         }
     }
 }
+
+/// gets the current vr chaperone interface (initialization is required beforehand)
+pub fn chaperone() -> Result<IVRChaperone, Error<openvr_sys::EVRInitError>> {
+    let mut err = EVRInitError_VRInitError_None;
+    let name = std::ffi::CString::new("FnTable:IVRChaperone_003").unwrap();
+    let ptr = unsafe {
+        openvr_sys::VR_GetGenericInterface(name.as_ptr(), &mut err)
+    };
+
+    match err {
+        EVRInitError_VRInitError_None => {
+            unsafe {
+                return Ok(IVRChaperone::from_raw(ptr as *const ()));
+            }
+        },
+        _ => {
+            return Err(Error::from_raw(err));
+        }
+    }
+}
+
+/// gets the current vr overlay interface (initialization is required beforehand)
+pub fn overlay() -> Result<IVROverlay, Error<openvr_sys::EVRInitError>> {
+    let mut err = EVRInitError_VRInitError_None;
+    let name = std::ffi::CString::new("FnTable:IVROverlay_013").unwrap();
+    let ptr = unsafe {
+        openvr_sys::VR_GetGenericInterface(name.as_ptr(), &mut err)
+    };
+
+    match err {
+        EVRInitError_VRInitError_None => {
+            unsafe {
+                return Ok(IVROverlay::from_raw(ptr as *const ()));
+            }
+        },
+        _ => {
+            return Err(Error::from_raw(err));
+        }
+    }
+}
+
+/// gets the current vr chaperone setup interface (initialization is required beforehand)
+pub fn chaperone_setup() -> Result<IVRChaperoneSetup, Error<openvr_sys::EVRInitError>> {
+    let mut err = EVRInitError_VRInitError_None;
+    let name = std::ffi::CString::new("FnTable:IVRChaperoneSetup_005").unwrap();
+    let ptr = unsafe {
+        openvr_sys::VR_GetGenericInterface(name.as_ptr(), &mut err)
+    };
+
+    match err {
+        EVRInitError_VRInitError_None => {
+            unsafe {
+                return Ok(IVRChaperoneSetup::from_raw(ptr as *const ()));
+            }
+        },
+        _ => {
+            return Err(Error::from_raw(err));
+        }
+    }
+}
+
+/// gets the current vr applications interface (initialization is required beforehand)
+pub fn applications() -> Result<IVRApplications, Error<openvr_sys::EVRInitError>> {
+    let mut err = EVRInitError_VRInitError_None;
+    let name = std::ffi::CString::new("FnTable:IVRApplications_006").unwrap();
+    let ptr = unsafe {
+        openvr_sys::VR_GetGenericInterface(name.as_ptr(), &mut err)
+    };
+
+    match err {
+        EVRInitError_VRInitError_None => {
+            unsafe {
+                return Ok(IVRApplications::from_raw(ptr as *const ()));
+            }
+        },
+        _ => {
+            return Err(Error::from_raw(err));
+        }
+    }
+}
+
+/// gets the current vr settings interface (initialization is required beforehand)
+pub fn settings() -> Result<IVRSettings, Error<openvr_sys::EVRInitError>> {
+    let mut err = EVRInitError_VRInitError_None;
+    let name = std::ffi::CString::new("FnTable:IVRSettings_001").unwrap();
+    let ptr = unsafe {
+        openvr_sys::VR_GetGenericInterface(name.as_ptr(), &mut err)
+    };
+
+    match err {
+        EVRInitError_VRInitError_None => {
+            unsafe {
+                return Ok(IVRSettings::from_raw(ptr as *const ()));
+            }
+        },
+        _ => {
+            return Err(Error::from_raw(err));
+        }
+    }
+}
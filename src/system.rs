@@ -1,12 +1,56 @@
 use openvr_sys;
 use openvr_sys::EGraphicsAPIConvention::*;
-use openvr_sys::ETrackingUniverseOrigin::*;
+use openvr_sys::ETrackedPropertyError::*;
+
+use std::time::Duration;
 
 use common::*;
 use tracking::*;
+use event::*;
+use error::*;
+use constants::*;
+use math;
 
 pub struct IVRSystem(pub *const ());
 
+/// converts the duration of a haptic pulse into microseconds, clamped to the device limit of 65535
+fn haptic_pulse_micros(duration: Duration) -> u16 {
+    let micros = duration.as_secs()
+        .saturating_mul(1_000_000)
+        .saturating_add((duration.subsec_nanos() / 1_000) as u64);
+
+    if micros > 65535 { 65535 } else { micros as u16 }
+}
+
+/// Iterates over the pending events of the system, see IVRSystem::poll_events
+pub struct SystemEventIterator<'a> {
+    system: &'a IVRSystem
+}
+
+impl<'a> Iterator for SystemEventIterator<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        use std;
+
+        unsafe {
+            let system = * { self.system.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut event: openvr_sys::VREvent_t = std::mem::zeroed();
+
+            let found = system.PollNextEvent.unwrap()(
+                &mut event,
+                std::mem::size_of::<openvr_sys::VREvent_t>() as u32
+            );
+
+            if found > 0 {
+                Some(Event::from_raw(event))
+            } else {
+                None
+            }
+        }
+    }
+}
+
 impl IVRSystem {
     pub unsafe fn from_raw(ptr: *const ()) -> Self {
         IVRSystem(ptr as *mut ())
@@ -30,6 +74,7 @@ impl IVRSystem {
     /// Get the projection matrix for an eye
     /// supply the near and the far position
     /// assumes opengl conventions
+    /// the matrix is row major as returned by openvr, mat[row][column]
     pub fn projection_matrix(&self, eye: Eye, near: f32, far: f32) -> [[f32; 4]; 4] {
         unsafe {
             let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
@@ -40,11 +85,33 @@ impl IVRSystem {
                 far,
                 EGraphicsAPIConvention_API_OpenGL
             );
-            mat.m
+            math::mat44_to_array(&mat)
+        }
+    }
+
+    /// Get the raw frustum of an eye as tangents of the half angles
+    ///  useful to build a custom projection matrix (for example with reversed depth)
+    ///  left and top are usually negative, as openvr uses opengl conventions
+    pub fn projection_raw(&self, eye: Eye) -> ProjectionRaw {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+
+            let mut raw = ProjectionRaw { left: 0.0, right: 0.0, top: 0.0, bottom: 0.0 };
+            system.GetProjectionRaw.unwrap()(
+                eye.to_raw(),
+                &mut raw.left,
+                &mut raw.right,
+                &mut raw.top,
+                &mut raw.bottom
+            );
+            raw
         }
     }
 
     /// Computes the distortion caused by the optics
+    ///  u and v are the coordinates on the screen of the eye (0 to 1),
+    ///  the result holds the coordinates to sample the rendered image at for each color channel
+    ///  the bound openvr version can not report a failure for this call
     pub fn compute_distortion(&self, eye: Eye, u: f32, v: f32) -> DistortionCoordinates {
         unsafe {
             let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
@@ -60,18 +127,20 @@ impl IVRSystem {
         }
     }
 
-    /// Computes the distortion caused by the optics
+    /// Get the transform from the eye space to the head space
+    /// the 3x4 matrix is row major as returned by openvr, the last column holds the translation
     pub fn eye_to_head_transform(&self, eye: Eye) -> [[f32; 4]; 3] {
         unsafe {
             let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
             let mat = system.GetEyeToHeadTransform.unwrap()(
                 eye.to_raw(),
             );
-            mat.m
+            math::mat34_to_array(&mat)
         }
     }
 
-    /// Computes the distortion caused by the optics
+    /// Get the seconds since the last vsync and the current frame counter
+    ///  None when the runtime has no valid timing yet
     pub fn time_since_last_vsync(&self) -> Option<(f32, u64)> {
         unsafe {
             let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
@@ -95,18 +164,704 @@ impl IVRSystem {
     /// Time is counted in photons, see https://github.com/ValveSoftware/openvr/wiki/IVRSystem::GetDeviceToAbsoluteTrackingPose
     ///  for time to photons conversion
     pub fn tracked_devices(&self, time: f32) -> TrackedDevicePoses {
+        self.device_poses(TrackingUniverseOrigin::Seated, time)
+    }
+
+    /// Fetch the tracked results relative to the given tracking space
+    /// predicted_seconds works like the time of tracked_devices
+    pub fn device_poses(&self, origin: TrackingUniverseOrigin, predicted_seconds: f32) -> TrackedDevicePoses {
         use std;
 
         unsafe {
             let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
-            let mut data: [openvr_sys::TrackedDevicePose_t; 16] = std::mem::zeroed();
+            let mut data: [openvr_sys::TrackedDevicePose_t; MAX_TRACKED_DEVICE_COUNT as usize] = std::mem::zeroed();
             system.GetDeviceToAbsoluteTrackingPose.unwrap()(
-                ETrackingUniverseOrigin_TrackingUniverseSeated,
-                time,
+                origin.to_raw(),
+                predicted_seconds,
                 &mut data[0],
-                16
+                MAX_TRACKED_DEVICE_COUNT
             );
             to_tracked(data)
         }
     }
+
+    /// Sets the current position and orientation of the hmd as the zero pose of the seated tracking space
+    ///  used for a "recenter" option of seated experiences
+    pub fn reset_seated_zero_pose(&self) {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.ResetSeatedZeroPose.unwrap()();
+        }
+    }
+
+    /// Get the transform from the seated zero pose to the standing tracking space
+    ///  the 3x4 matrix is row major as returned by openvr, the last column holds the translation
+    pub fn seated_zero_pose_to_standing(&self) -> [[f32; 4]; 3] {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mat = system.GetSeatedZeroPoseToStandingAbsoluteTrackingPose.unwrap()();
+            math::mat34_to_array(&mat)
+        }
+    }
+
+    /// Get the transform from the raw tracking space to the standing tracking space
+    ///  the 3x4 matrix is row major as returned by openvr, the last column holds the translation
+    pub fn raw_zero_pose_to_standing(&self) -> [[f32; 4]; 3] {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mat = system.GetRawZeroPoseToStandingAbsoluteTrackingPose.unwrap()();
+            math::mat34_to_array(&mat)
+        }
+    }
+
+    /// Returns an iterator that pops all pending events from the event queue
+    pub fn poll_events(&self) -> SystemEventIterator {
+        SystemEventIterator { system: self }
+    }
+
+    /// Returns the device class of a tracked device
+    pub fn tracked_device_class(&self, index: u32) -> TrackedDeviceClass {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            TrackedDeviceClass::from_raw(system.GetTrackedDeviceClass.unwrap()(index))
+        }
+    }
+
+    /// Checks whether a tracked device is connected
+    pub fn is_connected(&self, index: u32) -> bool {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.IsTrackedDeviceConnected.unwrap()(index) > 0
+        }
+    }
+
+    /// Returns true while another process (like the dashboard) captured the input focus
+    ///  the application does not receive the controller input then and should hide its
+    ///  controllers, as the other process draws them
+    pub fn is_input_focus_captured_by_another_process(&self) -> bool {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.IsInputFocusCapturedByAnotherProcess.unwrap()() > 0
+        }
+    }
+
+    /// Tells the runtime that the application is shutting down after a quit event
+    ///  gives the application some extra time to exit before it is killed
+    pub fn acknowledge_quit_exiting(&self) {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.AcknowledgeQuit_Exiting.unwrap()();
+        }
+    }
+
+    /// Returns how actively the device is used, for example to hide idle controllers
+    pub fn activity_level(&self, index: u32) -> DeviceActivityLevel {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            DeviceActivityLevel::from_raw(system.GetTrackedDeviceActivityLevel.unwrap()(index))
+        }
+    }
+
+    /// Returns the hand the controller is held in
+    pub fn controller_role(&self, index: u32) -> ControllerRole {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            ControllerRole::from_raw(system.GetControllerRoleForTrackedDeviceIndex.unwrap()(index))
+        }
+    }
+
+    /// Returns the index of the controller held in the given hand
+    ///  None when no controller has the role
+    pub fn device_index_for_role(&self, role: ControllerRole) -> Option<u32> {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let index = system.GetTrackedDeviceIndexForControllerRole.unwrap()(role.to_raw());
+
+            if index == TRACKED_DEVICE_INDEX_INVALID {
+                None
+            } else {
+                Some(index)
+            }
+        }
+    }
+
+    /// Returns the current state of the buttons and axes of a controller
+    ///  None when the device is not a controller or not connected
+    pub fn controller_state(&self, index: u32) -> Option<ControllerState> {
+        use std;
+
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut state: openvr_sys::VRControllerState_t = std::mem::zeroed();
+            let found = system.GetControllerState.unwrap()(
+                index,
+                &mut state
+            );
+
+            if found > 0 {
+                Some(ControllerState::from_raw(state))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns the state of a controller together with its pose, both taken at the same time
+    ///  None when the device is not a controller or not connected
+    pub fn controller_state_with_pose(&self, origin: TrackingUniverseOrigin, index: u32) -> Option<(ControllerState, TrackedDevicePose)> {
+        use std;
+
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut state: openvr_sys::VRControllerState_t = std::mem::zeroed();
+            let mut pose: openvr_sys::TrackedDevicePose_t = std::mem::zeroed();
+            let found = system.GetControllerStateWithPose.unwrap()(
+                origin.to_raw(),
+                index,
+                &mut state,
+                &mut pose
+            );
+
+            if found > 0 {
+                Some((ControllerState::from_raw(state), TrackedDevicePose::from_raw(index as usize, pose)))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Triggers a single haptic pulse on a controller
+    ///  axis selects the actuator, 0 for the vive controllers
+    ///  the duration is clamped to the device limit of 65535 microseconds
+    ///  openvr ignores further pulses on the same axis for about 5ms, so longer
+    ///  vibrations have to be created by calling this once per frame
+    pub fn trigger_haptic_pulse(&self, index: u32, axis: u32, duration: Duration) {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            system.TriggerHapticPulse.unwrap()(
+                index,
+                axis,
+                haptic_pulse_micros(duration)
+            );
+        }
+    }
+
+    /// Gets a string property of a tracked device
+    ///  properties longer than MAX_PROPERTY_STRING_SIZE return the TrackedProp_StringExceedsMaximumLength error,
+    ///  values that are not valid utf-8 the TrackedProp_WrongDataType error
+    pub fn get_string_property(&self, index: u32, property: TrackedDeviceStringProperty) -> Result<String, Error<openvr_sys::ETrackedPropertyError>> {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let get_property = system.GetStringTrackedDeviceProperty.unwrap();
+
+            // only the error of the last call is relevant, the sizing call reports a too small buffer
+            let mut err = ETrackedPropertyError_TrackedProp_Success;
+            let result = read_sized_string(Some(MAX_PROPERTY_STRING_SIZE), |buffer, len| {
+                err = ETrackedPropertyError_TrackedProp_Success;
+                get_property(index, property.to_raw(), buffer, len, &mut err)
+            });
+
+            match result {
+                Ok(value) => {
+                    let error = Error::from_raw(err);
+                    if error.is_err() { Err(error) } else { Ok(value) }
+                },
+                Err(ReadStringError::TooLong) => Err(Error::from_raw(ETrackedPropertyError_TrackedProp_StringExceedsMaximumLength)),
+                Err(ReadStringError::SizeChanged) => Err(Error::from_raw(ETrackedPropertyError_TrackedProp_BufferTooSmall)),
+                Err(ReadStringError::InvalidUtf8) => Err(Error::from_raw(ETrackedPropertyError_TrackedProp_WrongDataType))
+            }
+        }
+    }
+
+    /// Gets the serial number of the hmd
+    pub fn hmd_serial_number(&self) -> Result<String, Error<openvr_sys::ETrackedPropertyError>> {
+        self.get_string_property(TRACKED_DEVICE_INDEX_HMD, TrackedDeviceStringProperty::SerialNumber)
+    }
+
+    /// Gets the model number of the hmd
+    pub fn hmd_model_number(&self) -> Result<String, Error<openvr_sys::ETrackedPropertyError>> {
+        self.get_string_property(TRACKED_DEVICE_INDEX_HMD, TrackedDeviceStringProperty::ModelNumber)
+    }
+
+    /// Gets the manufacturer name of the hmd
+    pub fn hmd_manufacturer(&self) -> Result<String, Error<openvr_sys::ETrackedPropertyError>> {
+        self.get_string_property(TRACKED_DEVICE_INDEX_HMD, TrackedDeviceStringProperty::ManufacturerName)
+    }
+
+    /// Gets a float property of a tracked device
+    pub fn get_float_property(&self, index: u32, property: TrackedDeviceFloatProperty) -> Result<f32, Error<openvr_sys::ETrackedPropertyError>> {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut err = ETrackedPropertyError_TrackedProp_Success;
+            let value = system.GetFloatTrackedDeviceProperty.unwrap()(index, property.to_raw(), &mut err);
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                return Ok(value);
+            } else {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Gets an int32 property of a tracked device
+    pub fn get_int32_property(&self, index: u32, property: TrackedDeviceInt32Property) -> Result<i32, Error<openvr_sys::ETrackedPropertyError>> {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut err = ETrackedPropertyError_TrackedProp_Success;
+            let value = system.GetInt32TrackedDeviceProperty.unwrap()(index, property.to_raw(), &mut err);
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                return Ok(value);
+            } else {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Gets a bool property of a tracked device
+    pub fn get_bool_property(&self, index: u32, property: TrackedDeviceBoolProperty) -> Result<bool, Error<openvr_sys::ETrackedPropertyError>> {
+        unsafe {
+            let system = * { self.0 as *mut openvr_sys::VR_IVRSystem_FnTable };
+            let mut err = ETrackedPropertyError_TrackedProp_Success;
+            let value = system.GetBoolTrackedDeviceProperty.unwrap()(index, property.to_raw(), &mut err);
+
+            let error = Error::from_raw(err);
+            if error.is_ok() {
+                return Ok(value > 0);
+            } else {
+                return Err(error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use stub;
+
+    fn system(table: &openvr_sys::VR_IVRSystem_FnTable) -> IVRSystem {
+        IVRSystem(stub::table_ptr(table))
+    }
+
+    #[test]
+    fn haptic_pulse_duration_is_clamped() {
+        assert_eq!(haptic_pulse_micros(Duration::from_millis(0)), 0);
+        assert_eq!(haptic_pulse_micros(Duration::new(0, 1_999)), 1);
+        assert_eq!(haptic_pulse_micros(Duration::from_millis(3)), 3000);
+        assert_eq!(haptic_pulse_micros(Duration::from_micros(65535)), 65535);
+        assert_eq!(haptic_pulse_micros(Duration::from_micros(65536)), 65535);
+        assert_eq!(haptic_pulse_micros(Duration::from_secs(u64::max_value())), 65535);
+    }
+
+    static INPUT_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn is_input_focus_captured() -> stub::Bool {
+        INPUT_CAPTURED.load(Ordering::SeqCst) as stub::Bool
+    }
+
+    #[test]
+    fn input_focus_is_read_from_the_runtime() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.IsInputFocusCapturedByAnotherProcess = stub!(is_input_focus_captured);
+        let system = system(&table);
+
+        INPUT_CAPTURED.store(false, Ordering::SeqCst);
+        assert!(!system.is_input_focus_captured_by_another_process());
+        INPUT_CAPTURED.store(true, Ordering::SeqCst);
+        assert!(system.is_input_focus_captured_by_another_process());
+    }
+
+    thread_local! {
+        static EVENTS: RefCell<VecDeque<openvr_sys::VREvent_t>> = RefCell::new(VecDeque::new());
+    }
+
+    unsafe extern "C" fn poll_next_event(event: *mut openvr_sys::VREvent_t, size: u32) -> stub::Bool {
+        assert_eq!(size as usize, ::std::mem::size_of::<openvr_sys::VREvent_t>());
+        match EVENTS.with(|events| events.borrow_mut().pop_front()) {
+            Some(next) => {
+                *event = next;
+                1
+            },
+            None => 0
+        }
+    }
+
+    #[test]
+    fn poll_events_drains_the_queue_in_order() {
+        let mut raw: openvr_sys::VREvent_t = unsafe { ::std::mem::zeroed() };
+        EVENTS.with(|events| {
+            let mut events = events.borrow_mut();
+            raw.eventType = EventType::ButtonPress.to_raw();
+            raw.trackedDeviceIndex = 1;
+            events.push_back(raw);
+            raw.eventType = EventType::Quit.to_raw();
+            raw.trackedDeviceIndex = 0;
+            events.push_back(raw);
+        });
+
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.PollNextEvent = stub!(poll_next_event);
+        let system = system(&table);
+
+        let drained: Vec<(EventType, u32)> = system.poll_events().map(|event| (event.event_type, event.tracked_device_index)).collect();
+        assert_eq!(drained, vec![(EventType::ButtonPress, 1), (EventType::Quit, 0)]);
+        assert_eq!(system.poll_events().count(), 0);
+    }
+
+    unsafe extern "C" fn get_device_to_absolute_tracking_pose(origin: openvr_sys::ETrackingUniverseOrigin, predicted_seconds: f32, poses: *mut openvr_sys::TrackedDevicePose_t, count: u32) {
+        stub::record(format!("{:?} {}", TrackingUniverseOrigin::from_raw(origin), predicted_seconds));
+        assert_eq!(count, MAX_TRACKED_DEVICE_COUNT);
+
+        // the hmd and a controller at index 2 are tracked
+        for &index in &[0usize, 2] {
+            let pose = &mut *poses.add(index);
+            pose.bDeviceIsConnected = 1;
+            pose.bPoseIsValid = 1;
+            pose.mDeviceToAbsoluteTracking.m = [[1.0, 0.0, 0.0, index as f32], [0.0, 1.0, 0.0, 1.5], [0.0, 0.0, 1.0, -0.5]];
+            pose.vVelocity.v = [0.0, 0.0, index as f32];
+            pose.vAngularVelocity.v = [0.5, 0.0, 0.0];
+            pose.eTrackingResult = openvr_sys::ETrackingResult::ETrackingResult_TrackingResult_Running_OK;
+        }
+    }
+
+    #[test]
+    fn device_poses_reads_the_stubbed_pose_array() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetDeviceToAbsoluteTrackingPose = stub!(get_device_to_absolute_tracking_pose);
+        let system = system(&table);
+
+        let poses = system.device_poses(TrackingUniverseOrigin::Standing, 0.25);
+        assert_eq!(stub::take_calls(), vec!["Standing 0.25"]);
+
+        assert_eq!(poses.count, 3);
+        let controller = &poses.as_slice()[2];
+        assert_eq!(controller.index, 2);
+        assert!(controller.is_valid() && controller.is_connected());
+        assert_eq!(controller.position(), [2.0, 1.5, -0.5]);
+        assert_eq!(controller.velocity, [0.0, 0.0, 2.0]);
+        assert_eq!(controller.angular_velocity, [0.5, 0.0, 0.0]);
+        assert!(!poses.as_slice()[1].is_connected());
+
+        system.tracked_devices(0.0);
+        assert_eq!(stub::take_calls(), vec!["Seated 0"]);
+    }
+
+    unsafe extern "C" fn get_tracked_device_class(index: u32) -> openvr_sys::ETrackedDeviceClass {
+        use openvr_sys::ETrackedDeviceClass::*;
+
+        match index {
+            0 => ETrackedDeviceClass_TrackedDeviceClass_HMD,
+            1 => ETrackedDeviceClass_TrackedDeviceClass_Controller,
+            2 => ETrackedDeviceClass_TrackedDeviceClass_TrackingReference,
+            3 => ETrackedDeviceClass_TrackedDeviceClass_Other,
+            _ => ETrackedDeviceClass_TrackedDeviceClass_Invalid
+        }
+    }
+
+    #[test]
+    fn tracked_device_class_maps_each_raw_class() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetTrackedDeviceClass = stub!(get_tracked_device_class);
+        let system = system(&table);
+
+        let classes: Vec<TrackedDeviceClass> = (0..5).map(|index| system.tracked_device_class(index)).collect();
+        assert_eq!(classes, vec![
+            TrackedDeviceClass::HMD,
+            TrackedDeviceClass::Controller,
+            TrackedDeviceClass::TrackingReference,
+            TrackedDeviceClass::Other,
+            TrackedDeviceClass::Invalid
+        ]);
+    }
+
+    unsafe extern "C" fn is_tracked_device_connected(index: u32) -> stub::Bool {
+        (index == 1) as stub::Bool
+    }
+
+    #[test]
+    fn is_connected_reports_only_the_stubbed_device() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.IsTrackedDeviceConnected = stub!(is_tracked_device_connected);
+        let system = system(&table);
+
+        let connected: Vec<u32> = (0..MAX_TRACKED_DEVICE_COUNT).filter(|&index| system.is_connected(index)).collect();
+        assert_eq!(connected, vec![1]);
+    }
+
+    thread_local! {
+        static SIZE_CALLS: ::std::cell::Cell<u32> = ::std::cell::Cell::new(0);
+    }
+
+    // every string property of the stub behaves differently, see the property tests
+    unsafe extern "C" fn get_string_tracked_device_property(index: u32, property: openvr_sys::ETrackedDeviceProperty, buffer: *mut i8, len: u32, err: *mut openvr_sys::ETrackedPropertyError) -> u32 {
+        use openvr_sys::ETrackedDeviceProperty::*;
+
+        stub::record(format!("{} {:?} {}", index, buffer.is_null(), len));
+        let value = match property {
+            ETrackedDeviceProperty_Prop_SerialNumber_String => "LHR-0123",
+            ETrackedDeviceProperty_Prop_ModelNumber_String => "Vive MV",
+            ETrackedDeviceProperty_Prop_ManufacturerName_String => "HTC",
+            ETrackedDeviceProperty_Prop_ModeLabel_String => "",
+            ETrackedDeviceProperty_Prop_TrackingSystemName_String => {
+                // the value grows between the calls
+                let calls = SIZE_CALLS.with(|calls| { calls.set(calls.get() + 1); calls.get() });
+                *err = ETrackedPropertyError_TrackedProp_BufferTooSmall;
+                return 4 + calls;
+            },
+            ETrackedDeviceProperty_Prop_HardwareRevision_String => {
+                // the runtime reports a size above the limit
+                *err = ETrackedPropertyError_TrackedProp_BufferTooSmall;
+                return MAX_PROPERTY_STRING_SIZE + 1;
+            },
+            _ => {
+                *err = ETrackedPropertyError_TrackedProp_UnknownProperty;
+                return 0;
+            }
+        };
+
+        let required = stub::write_str(value, buffer, len);
+        *err = if len < required && required > 1 {
+            ETrackedPropertyError_TrackedProp_BufferTooSmall
+        } else {
+            ETrackedPropertyError_TrackedProp_Success
+        };
+        if value.is_empty() { 0 } else { required }
+    }
+
+    fn property_system(table: &mut openvr_sys::VR_IVRSystem_FnTable) -> IVRSystem {
+        table.GetStringTrackedDeviceProperty = stub!(get_string_tracked_device_property);
+        system(table)
+    }
+
+    #[test]
+    fn string_property_sizes_the_buffer_with_a_first_call() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        let system = property_system(&mut table);
+
+        assert_eq!(system.get_string_property(3, TrackedDeviceStringProperty::SerialNumber).unwrap(), "LHR-0123");
+        assert_eq!(stub::take_calls(), vec!["3 true 0", "3 false 9"]);
+    }
+
+    #[test]
+    fn string_property_errors_are_mapped() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        let system = property_system(&mut table);
+
+        let err = system.get_string_property(0, TrackedDeviceStringProperty::TrackingSystemName).unwrap_err();
+        assert_eq!(err.to_raw(), ETrackedPropertyError_TrackedProp_BufferTooSmall);
+
+        let err = system.get_string_property(0, TrackedDeviceStringProperty::AttachedDeviceId).unwrap_err();
+        assert_eq!(err.to_raw(), ETrackedPropertyError_TrackedProp_UnknownProperty);
+
+        stub::take_calls();
+        let err = system.get_string_property(0, TrackedDeviceStringProperty::HardwareRevision).unwrap_err();
+        assert_eq!(err.to_raw(), ETrackedPropertyError_TrackedProp_StringExceedsMaximumLength);
+        // the oversized buffer is never allocated
+        assert_eq!(stub::take_calls(), vec!["0 true 0"]);
+    }
+
+    #[test]
+    fn empty_string_property_is_ok() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        let system = property_system(&mut table);
+
+        assert_eq!(system.get_string_property(0, TrackedDeviceStringProperty::ModeLabel).unwrap(), "");
+        assert_eq!(stub::take_calls(), vec!["0 true 0"]);
+    }
+
+    unsafe extern "C" fn get_controller_state_with_pose(origin: openvr_sys::ETrackingUniverseOrigin, index: u32, state: *mut openvr_sys::VRControllerState_t, pose: *mut openvr_sys::TrackedDevicePose_t) -> stub::Bool {
+        stub::record(format!("{:?} {}", TrackingUniverseOrigin::from_raw(origin), index));
+        if index != 1 {
+            return 0;
+        }
+
+        (*state).unPacketNum = 12;
+        (*state).ulButtonPressed = Button::Grip.mask();
+        (*state).rAxis[1].x = 0.75;
+        (*pose).bDeviceIsConnected = 1;
+        (*pose).bPoseIsValid = 1;
+        (*pose).mDeviceToAbsoluteTracking.m = [[1.0, 0.0, 0.0, 0.1], [0.0, 1.0, 0.0, 1.2], [0.0, 0.0, 1.0, -0.3]];
+        (*pose).eTrackingResult = openvr_sys::ETrackingResult::ETrackingResult_TrackingResult_Running_OK;
+        1
+    }
+
+    #[test]
+    fn controller_state_with_pose_fills_both_outputs() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetControllerStateWithPose = stub!(get_controller_state_with_pose);
+        let system = system(&table);
+
+        let (state, pose) = system.controller_state_with_pose(TrackingUniverseOrigin::Standing, 1).unwrap();
+        assert_eq!(state.packet_number(), 12);
+        assert!(state.button_pressed(Button::Grip));
+        assert_eq!(state.axis(1), (0.75, 0.0));
+        assert_eq!(pose.index, 1);
+        assert!(pose.is_valid() && pose.is_connected());
+        assert_eq!(pose.position(), [0.1, 1.2, -0.3]);
+
+        assert!(system.controller_state_with_pose(TrackingUniverseOrigin::Seated, 4).is_none());
+        assert_eq!(stub::take_calls(), vec!["Standing 1", "Seated 4"]);
+    }
+
+    unsafe extern "C" fn get_projection_raw(eye: openvr_sys::EVREye, left: *mut f32, right: *mut f32, top: *mut f32, bottom: *mut f32) {
+        let sign = if eye == Eye::Left.to_raw() { -1.0 } else { 1.0 };
+        *left = -1.25 + sign * 0.125;
+        *right = 1.25 + sign * 0.125;
+        *top = -1.5;
+        *bottom = 1.4;
+    }
+
+    #[test]
+    fn projection_raw_reads_the_four_tangents() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetProjectionRaw = stub!(get_projection_raw);
+        let system = system(&table);
+
+        let left = system.projection_raw(Eye::Left);
+        assert_eq!((left.left, left.right, left.top, left.bottom), (-1.375, 1.125, -1.5, 1.4));
+        let right = system.projection_raw(Eye::Right);
+        assert_eq!((right.left, right.right, right.top, right.bottom), (-1.125, 1.375, -1.5, 1.4));
+    }
+
+    unsafe extern "C" fn get_tracked_device_activity_level(index: u32) -> openvr_sys::EDeviceActivityLevel {
+        use openvr_sys::EDeviceActivityLevel::*;
+
+        match index {
+            0 => EDeviceActivityLevel_k_EDeviceActivityLevel_Idle,
+            1 => EDeviceActivityLevel_k_EDeviceActivityLevel_UserInteraction,
+            2 => EDeviceActivityLevel_k_EDeviceActivityLevel_UserInteraction_Timeout,
+            3 => EDeviceActivityLevel_k_EDeviceActivityLevel_Standby,
+            _ => EDeviceActivityLevel_k_EDeviceActivityLevel_Unknown
+        }
+    }
+
+    #[test]
+    fn activity_level_maps_each_level() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetTrackedDeviceActivityLevel = stub!(get_tracked_device_activity_level);
+        let system = system(&table);
+
+        let levels: Vec<DeviceActivityLevel> = (0..5).map(|index| system.activity_level(index)).collect();
+        assert_eq!(levels, vec![
+            DeviceActivityLevel::Idle,
+            DeviceActivityLevel::UserInteraction,
+            DeviceActivityLevel::UserInteractionTimeout,
+            DeviceActivityLevel::Standby,
+            DeviceActivityLevel::Unknown
+        ]);
+    }
+
+    unsafe extern "C" fn get_controller_role_for_tracked_device_index(index: u32) -> openvr_sys::ETrackedControllerRole {
+        use openvr_sys::ETrackedControllerRole::*;
+
+        match index {
+            1 => ETrackedControllerRole_TrackedControllerRole_LeftHand,
+            2 => ETrackedControllerRole_TrackedControllerRole_RightHand,
+            _ => ETrackedControllerRole_TrackedControllerRole_Invalid
+        }
+    }
+
+    #[test]
+    fn controller_role_maps_each_role() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetControllerRoleForTrackedDeviceIndex = stub!(get_controller_role_for_tracked_device_index);
+        let system = system(&table);
+
+        assert_eq!(system.controller_role(0), ControllerRole::Invalid);
+        assert_eq!(system.controller_role(1), ControllerRole::LeftHand);
+        assert_eq!(system.controller_role(2), ControllerRole::RightHand);
+    }
+
+    unsafe extern "C" fn get_tracked_device_index_for_controller_role(role: openvr_sys::ETrackedControllerRole) -> u32 {
+        match ControllerRole::from_raw(role) {
+            ControllerRole::LeftHand => 3,
+            _ => TRACKED_DEVICE_INDEX_INVALID
+        }
+    }
+
+    #[test]
+    fn device_index_for_role_found_and_not_found() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetTrackedDeviceIndexForControllerRole = stub!(get_tracked_device_index_for_controller_role);
+        let system = system(&table);
+
+        assert_eq!(system.device_index_for_role(ControllerRole::LeftHand), Some(3));
+        assert_eq!(system.device_index_for_role(ControllerRole::RightHand), None);
+    }
+
+    unsafe extern "C" fn acknowledge_quit_exiting() {
+        stub::record("AcknowledgeQuit_Exiting");
+    }
+
+    #[test]
+    fn acknowledge_quit_exiting_calls_the_runtime() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.AcknowledgeQuit_Exiting = stub!(acknowledge_quit_exiting);
+        let system = system(&table);
+
+        system.acknowledge_quit_exiting();
+        assert_eq!(stub::take_calls(), vec!["AcknowledgeQuit_Exiting"]);
+    }
+
+    #[test]
+    fn hmd_shortcuts_read_the_hmd_properties() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        let system = property_system(&mut table);
+
+        assert_eq!(system.hmd_serial_number().unwrap(), "LHR-0123");
+        assert_eq!(system.hmd_model_number().unwrap(), "Vive MV");
+        assert_eq!(system.hmd_manufacturer().unwrap(), "HTC");
+
+        let prefix = format!("{} ", TRACKED_DEVICE_INDEX_HMD);
+        let calls = stub::take_calls();
+        assert_eq!(calls.len(), 6);
+        assert!(calls.iter().all(|call| call.starts_with(&prefix)), "{:?}", calls);
+    }
+
+    // row major with distinct entries, so a transposed conversion would fail
+    const ZERO_POSE: [[f32; 4]; 3] = [[0.0, -1.0, 0.0, 0.25], [1.0, 0.0, 0.0, 1.5], [0.0, 0.0, 1.0, -2.0]];
+
+    unsafe extern "C" fn reset_seated_zero_pose() {
+        stub::record("ResetSeatedZeroPose");
+    }
+
+    unsafe extern "C" fn get_seated_zero_pose_to_standing_absolute_tracking_pose() -> openvr_sys::HmdMatrix34_t {
+        openvr_sys::HmdMatrix34_t { m: ZERO_POSE }
+    }
+
+    #[test]
+    fn seated_zero_pose_reset_and_transform() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.ResetSeatedZeroPose = stub!(reset_seated_zero_pose);
+        table.GetSeatedZeroPoseToStandingAbsoluteTrackingPose = stub!(get_seated_zero_pose_to_standing_absolute_tracking_pose);
+        let system = system(&table);
+
+        system.reset_seated_zero_pose();
+        assert_eq!(stub::take_calls(), vec!["ResetSeatedZeroPose"]);
+        assert_eq!(system.seated_zero_pose_to_standing(), ZERO_POSE);
+    }
+
+    unsafe extern "C" fn get_raw_zero_pose_to_standing_absolute_tracking_pose() -> openvr_sys::HmdMatrix34_t {
+        let mut m = ZERO_POSE;
+        m[2][3] = 3.0;
+        openvr_sys::HmdMatrix34_t { m }
+    }
+
+    #[test]
+    fn raw_zero_pose_matrix_extraction() {
+        let mut table = stub::empty_table::<openvr_sys::VR_IVRSystem_FnTable>();
+        table.GetRawZeroPoseToStandingAbsoluteTrackingPose = stub!(get_raw_zero_pose_to_standing_absolute_tracking_pose);
+        let system = system(&table);
+
+        let m = system.raw_zero_pose_to_standing();
+        assert_eq!(m[0], ZERO_POSE[0]);
+        assert_eq!(m[1], ZERO_POSE[1]);
+        assert_eq!(m[2], [0.0, 0.0, 1.0, 3.0]);
+    }
 }
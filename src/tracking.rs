@@ -1,10 +1,8 @@
 use openvr_sys;
-use openvr_sys::ETrackedPropertyError::*;
 
 use subsystems::*;
 use error::*;
-use std::slice;
-use std::str;
+use constants::*;
 
 /// Describes a string property of a tracked device
 #[derive(Debug, Copy, Clone)]
@@ -55,8 +53,88 @@ impl TrackedDeviceStringProperty {
     }
 }
 
-/// Describes the class of a tracked device
+/// Describes a float property of a tracked device
+#[derive(Debug, Copy, Clone)]
+pub enum TrackedDeviceFloatProperty {
+    SecondsFromVsyncToPhotons,
+    DisplayFrequency,
+    UserIpdMeters,
+    DeviceBatteryPercentage
+}
+
+impl TrackedDeviceFloatProperty {
+    pub fn to_raw(&self) -> openvr_sys::ETrackedDeviceProperty {
+        use openvr_sys::ETrackedDeviceProperty::*;
+        use self::TrackedDeviceFloatProperty::*;
+
+        match *self {
+            SecondsFromVsyncToPhotons => ETrackedDeviceProperty_Prop_SecondsFromVsyncToPhotons_Float,
+            DisplayFrequency => ETrackedDeviceProperty_Prop_DisplayFrequency_Float,
+            UserIpdMeters => ETrackedDeviceProperty_Prop_UserIpdMeters_Float,
+            DeviceBatteryPercentage => ETrackedDeviceProperty_Prop_DeviceBatteryPercentage_Float
+        }
+    }
+}
+
+/// Describes an int32 property of a tracked device
+#[derive(Debug, Copy, Clone)]
+pub enum TrackedDeviceInt32Property {
+    DeviceClass,
+    Axis0Type,
+    Axis1Type,
+    Axis2Type,
+    Axis3Type,
+    Axis4Type
+}
+
+impl TrackedDeviceInt32Property {
+    pub fn to_raw(&self) -> openvr_sys::ETrackedDeviceProperty {
+        use openvr_sys::ETrackedDeviceProperty::*;
+        use self::TrackedDeviceInt32Property::*;
+
+        match *self {
+            DeviceClass => ETrackedDeviceProperty_Prop_DeviceClass_Int32,
+            Axis0Type => ETrackedDeviceProperty_Prop_Axis0Type_Int32,
+            Axis1Type => ETrackedDeviceProperty_Prop_Axis1Type_Int32,
+            Axis2Type => ETrackedDeviceProperty_Prop_Axis2Type_Int32,
+            Axis3Type => ETrackedDeviceProperty_Prop_Axis3Type_Int32,
+            Axis4Type => ETrackedDeviceProperty_Prop_Axis4Type_Int32
+        }
+    }
+}
+
+/// Describes a bool property of a tracked device
 #[derive(Debug, Copy, Clone)]
+pub enum TrackedDeviceBoolProperty {
+    WillDriftInYaw,
+    DeviceIsWireless,
+    DeviceIsCharging,
+    DeviceProvidesBatteryStatus,
+    DeviceCanPowerOff,
+    ContainsProximitySensor,
+    HasCamera
+}
+
+impl TrackedDeviceBoolProperty {
+    pub fn to_raw(&self) -> openvr_sys::ETrackedDeviceProperty {
+        use openvr_sys::ETrackedDeviceProperty::*;
+        use self::TrackedDeviceBoolProperty::*;
+
+        match *self {
+            WillDriftInYaw => ETrackedDeviceProperty_Prop_WillDriftInYaw_Bool,
+            DeviceIsWireless => ETrackedDeviceProperty_Prop_DeviceIsWireless_Bool,
+            DeviceIsCharging => ETrackedDeviceProperty_Prop_DeviceIsCharging_Bool,
+            DeviceProvidesBatteryStatus => ETrackedDeviceProperty_Prop_DeviceProvidesBatteryStatus_Bool,
+            DeviceCanPowerOff => ETrackedDeviceProperty_Prop_DeviceCanPowerOff_Bool,
+            ContainsProximitySensor => ETrackedDeviceProperty_Prop_ContainsProximitySensor_Bool,
+            HasCamera => ETrackedDeviceProperty_Prop_HasCamera_Bool
+        }
+    }
+}
+
+/// Describes the class of a tracked device
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TrackedDeviceClass {
     Invalid,
     HMD,
@@ -93,14 +171,156 @@ impl TrackedDeviceClass {
     }
 }
 
+/// Describes how actively a device is used, see IVRSystem::activity_level
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeviceActivityLevel {
+    Unknown,
+    /// no activity for the last 10 seconds
+    Idle,
+    /// the device is in use
+    UserInteraction,
+    /// no activity for the last 0.5 seconds
+    UserInteractionTimeout,
+    /// the hmd has been idle for the standby timeout
+    Standby,
+}
+
+impl DeviceActivityLevel {
+    pub fn from_raw(raw: openvr_sys::EDeviceActivityLevel) -> Self {
+        use self::DeviceActivityLevel::*;
+        use openvr_sys::EDeviceActivityLevel::*;
+
+        match raw {
+            EDeviceActivityLevel_k_EDeviceActivityLevel_Unknown => Unknown,
+            EDeviceActivityLevel_k_EDeviceActivityLevel_Idle => Idle,
+            EDeviceActivityLevel_k_EDeviceActivityLevel_UserInteraction => UserInteraction,
+            EDeviceActivityLevel_k_EDeviceActivityLevel_UserInteraction_Timeout => UserInteractionTimeout,
+            EDeviceActivityLevel_k_EDeviceActivityLevel_Standby => Standby,
+        }
+    }
+}
+
+/// Describes which hand a controller is held in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ControllerRole {
+    /// the device is not a controller or the hand is unknown
+    Invalid,
+    LeftHand,
+    RightHand,
+}
+
+impl ControllerRole {
+    pub fn to_raw(&self) -> openvr_sys::ETrackedControllerRole {
+        use self::ControllerRole::*;
+        use openvr_sys::ETrackedControllerRole::*;
+
+        match *self {
+            Invalid => ETrackedControllerRole_TrackedControllerRole_Invalid,
+            LeftHand => ETrackedControllerRole_TrackedControllerRole_LeftHand,
+            RightHand => ETrackedControllerRole_TrackedControllerRole_RightHand,
+        }
+    }
+
+    pub fn from_raw(raw: openvr_sys::ETrackedControllerRole) -> Self {
+        use self::ControllerRole::*;
+        use openvr_sys::ETrackedControllerRole::*;
+
+        match raw {
+            ETrackedControllerRole_TrackedControllerRole_Invalid => Invalid,
+            ETrackedControllerRole_TrackedControllerRole_LeftHand => LeftHand,
+            ETrackedControllerRole_TrackedControllerRole_RightHand => RightHand,
+        }
+    }
+}
+
+/// Describes the origin of the tracking space poses are relative to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TrackingUniverseOrigin {
+    /// poses are relative to the seated zero pose
+    Seated,
+    /// poses are relative to the safe bounds configured by the user
+    Standing,
+    /// poses are relative to the raw, uncalibrated tracking space
+    RawAndUncalibrated,
+}
+
+impl TrackingUniverseOrigin {
+    pub fn to_raw(&self) -> openvr_sys::ETrackingUniverseOrigin {
+        use self::TrackingUniverseOrigin::*;
+        use openvr_sys::ETrackingUniverseOrigin::*;
+
+        match *self {
+            Seated => ETrackingUniverseOrigin_TrackingUniverseSeated,
+            Standing => ETrackingUniverseOrigin_TrackingUniverseStanding,
+            RawAndUncalibrated => ETrackingUniverseOrigin_TrackingUniverseRawAndUncalibrated,
+        }
+    }
+
+    pub fn from_raw(raw: openvr_sys::ETrackingUniverseOrigin) -> Self {
+        use self::TrackingUniverseOrigin::*;
+        use openvr_sys::ETrackingUniverseOrigin::*;
+
+        match raw {
+            ETrackingUniverseOrigin_TrackingUniverseSeated => Seated,
+            ETrackingUniverseOrigin_TrackingUniverseStanding => Standing,
+            ETrackingUniverseOrigin_TrackingUniverseRawAndUncalibrated => RawAndUncalibrated,
+        }
+    }
+}
+
+impl From<openvr_sys::ETrackingUniverseOrigin> for TrackingUniverseOrigin {
+    fn from(raw: openvr_sys::ETrackingUniverseOrigin) -> Self {
+        TrackingUniverseOrigin::from_raw(raw)
+    }
+}
+
+impl From<TrackingUniverseOrigin> for openvr_sys::ETrackingUniverseOrigin {
+    fn from(origin: TrackingUniverseOrigin) -> Self {
+        origin.to_raw()
+    }
+}
+
+/// Describes the quality of the tracking of a device
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TrackingResult {
+    Uninitialized,
+    CalibratingInProgress,
+    CalibratingOutOfRange,
+    /// the device is tracked normally
+    RunningOk,
+    /// the device is outside of the tracked area, the pose is extrapolated
+    RunningOutOfRange,
+}
+
+impl TrackingResult {
+    pub fn from_raw(raw: openvr_sys::ETrackingResult) -> Self {
+        use self::TrackingResult::*;
+        use openvr_sys::ETrackingResult::*;
+
+        match raw {
+            ETrackingResult_TrackingResult_Uninitialized => Uninitialized,
+            ETrackingResult_TrackingResult_Calibrating_InProgress => CalibratingInProgress,
+            ETrackingResult_TrackingResult_Calibrating_OutOfRange => CalibratingOutOfRange,
+            ETrackingResult_TrackingResult_Running_OK => RunningOk,
+            ETrackingResult_TrackingResult_Running_OutOfRange => RunningOutOfRange,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TrackedDevicePose {
     pub index: usize,
     pub to_device: [[f32; 4]; 3],
     pub velocity: [f32; 3],
     pub angular_velocity: [f32; 3],
-    pub is_valid: bool,
-    pub is_connected: bool,
+    is_valid: bool,
+    is_connected: bool,
+    tracking_result: TrackingResult,
 }
 
 impl TrackedDevicePose {
@@ -112,50 +332,183 @@ impl TrackedDevicePose {
             to_device: d.mDeviceToAbsoluteTracking.m,
             velocity: d.vVelocity.v,
             angular_velocity: d.vAngularVelocity.v,
+            tracking_result: TrackingResult::from_raw(d.eTrackingResult),
+        }
+    }
+
+    /// returns true when the pose can be used, invalid poses should not be rendered
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    /// returns true when the device is connected, even if it is not tracked right now
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    /// returns the quality of the tracking, e.g. to gray out devices that left the tracked area
+    pub fn tracking_result(&self) -> TrackingResult {
+        self.tracking_result
+    }
+
+    /// returns the position of the device, the translation column of to_device
+    pub fn position(&self) -> [f32; 3] {
+        [self.to_device[0][3], self.to_device[1][3], self.to_device[2][3]]
+    }
+
+    /// returns the rotation of the device as (yaw, pitch, roll) in radians
+    ///  yaw rotates around y (up), pitch around x (right) and roll around z (backwards),
+    ///  applied in the order yaw * pitch * roll (so roll is applied to the device first)
+    pub fn euler_angles(&self) -> (f32, f32, f32) {
+        let m = &self.to_device;
+
+        let sin_pitch = -m[1][2];
+        let sin_pitch = if sin_pitch > 1.0 { 1.0 } else if sin_pitch < -1.0 { -1.0 } else { sin_pitch };
+        let pitch = sin_pitch.asin();
+
+        if sin_pitch.abs() > 0.9999 {
+            // looking straight up or down, yaw and roll rotate around the same axis
+            ((-m[2][0]).atan2(m[0][0]), pitch, 0.0)
+        } else {
+            (m[0][2].atan2(m[2][2]), pitch, m[1][0].atan2(m[1][1]))
         }
     }
 
     // returns the device class of the tracked object
     pub fn device_class(&self) -> TrackedDeviceClass {
-        unsafe {
-            let system = * { system().unwrap().0 as *mut openvr_sys::VR_IVRSystem_FnTable};
-            TrackedDeviceClass::from_raw(system.GetTrackedDeviceClass.unwrap()(self.index as u32))
-        }
+        system().unwrap().tracked_device_class(self.index as u32)
     }
 
     /// gets a propery as a string
     pub fn get_property_string(&self, property: TrackedDeviceStringProperty) -> Result<String, Error<openvr_sys::ETrackedPropertyError>> {
+        system().unwrap().get_string_property(self.index as u32, property)
+    }
+}
+
+/// Describes a button of a controller, see EVRButtonId
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Button {
+    System,
+    ApplicationMenu,
+    Grip,
+    DPadLeft,
+    DPadUp,
+    DPadRight,
+    DPadDown,
+    A,
+    ProximitySensor,
+    /// the touchpad on the vive controllers
+    Axis0,
+    /// the trigger on the vive controllers
+    Axis1,
+    Axis2,
+    Axis3,
+    Axis4,
+}
+
+impl Button {
+    /// Returns the raw button id
+    pub fn id(&self) -> u32 {
+        use self::Button::*;
+
+        match *self {
+            System => 0,
+            ApplicationMenu => 1,
+            Grip => 2,
+            DPadLeft => 3,
+            DPadUp => 4,
+            DPadRight => 5,
+            DPadDown => 6,
+            A => 7,
+            ProximitySensor => 31,
+            Axis0 => 32,
+            Axis1 => 33,
+            Axis2 => 34,
+            Axis3 => 35,
+            Axis4 => 36,
+        }
+    }
+
+    /// Returns the bit of the button inside the pressed and touched masks
+    pub fn mask(&self) -> u64 {
+        1u64 << self.id()
+    }
+}
+
+/// Describes the state of the buttons and axes of a controller
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControllerState {
+    pub packet_num: u32,
+    pub pressed_mask: u64,
+    pub touched_mask: u64,
+    pub axes: [[f32; 2]; CONTROLLER_STATE_AXIS_COUNT as usize],
+}
+
+impl ControllerState {
+    pub fn from_raw(raw: openvr_sys::VRControllerState_t) -> Self {
+        let mut axes = [[0.0; 2]; CONTROLLER_STATE_AXIS_COUNT as usize];
+        for (i, axis) in raw.rAxis.iter().enumerate() {
+            axes[i] = [axis.x, axis.y];
+        }
+
+        ControllerState {
+            packet_num: raw.unPacketNum,
+            pressed_mask: raw.ulButtonPressed,
+            touched_mask: raw.ulButtonTouched,
+            axes: axes,
+        }
+    }
+
+    pub fn to_raw(&self) -> openvr_sys::VRControllerState_t {
+        use std;
+
         unsafe {
-            let system = * { system().unwrap().0 as *mut openvr_sys::VR_IVRSystem_FnTable};
-
-            let val_out = String::with_capacity(256);
-            let mut err = ETrackedPropertyError_TrackedProp_Success;
-
-            let size = system.GetStringTrackedDeviceProperty.unwrap()(
-                self.index as u32,
-                property.to_raw(),
-                val_out.as_ptr() as *mut i8,
-                256,
-                &mut err
-            );
-
-            if size > 0 {
-                let ptr = val_out.as_ptr() as *mut u8;
-                let mem = slice::from_raw_parts(ptr, size as usize);
-                let str = str::from_utf8(mem).unwrap();
-                return Ok(String::from(str));
-            } else {
-                return Err(Error::from_raw(err));
+            let mut raw: openvr_sys::VRControllerState_t = std::mem::zeroed();
+            raw.unPacketNum = self.packet_num;
+            raw.ulButtonPressed = self.pressed_mask;
+            raw.ulButtonTouched = self.touched_mask;
+            for (i, axis) in self.axes.iter().enumerate() {
+                raw.rAxis[i].x = axis[0];
+                raw.rAxis[i].y = axis[1];
             }
+            raw
         }
     }
-}
 
+    /// Returns the packet number, openvr increments it whenever the state changes
+    pub fn packet_number(&self) -> u32 {
+        self.packet_num
+    }
+
+    /// Returns true when the state differs from the one with the previous packet number
+    pub fn has_changed_since(&self, prev: u32) -> bool {
+        self.packet_num != prev
+    }
+
+    /// Returns true when the button is pressed down
+    pub fn button_pressed(&self, button: Button) -> bool {
+        self.pressed_mask & button.mask() != 0
+    }
+
+    /// Returns true when the button is touched
+    pub fn button_touched(&self, button: Button) -> bool {
+        self.touched_mask & button.mask() != 0
+    }
+
+    /// Returns the x and y value of an axis, i has to be smaller than CONTROLLER_STATE_AXIS_COUNT
+    ///  the trigger only uses x, ranging from 0 to 1
+    pub fn axis(&self, i: usize) -> (f32, f32) {
+        (self.axes[i][0], self.axes[i][1])
+    }
+}
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TrackedDevicePoses {
     pub count: usize,
-    pub poses: [TrackedDevicePose; 16],
+    pub poses: [TrackedDevicePose; MAX_TRACKED_DEVICE_COUNT as usize],
 }
 
 pub struct TrackedDevicePosesIterator<'a> {
@@ -194,19 +547,208 @@ impl<'a> Iterator for TrackedDevicePosesIterator<'a> {
     }
 }
 
-pub unsafe fn to_tracked(data: [openvr_sys::TrackedDevicePose_t; 16]) -> TrackedDevicePoses {
+pub unsafe fn to_tracked(data: [openvr_sys::TrackedDevicePose_t; MAX_TRACKED_DEVICE_COUNT as usize]) -> TrackedDevicePoses {
     use std;
     let mut out: TrackedDevicePoses = std::mem::zeroed();
     for (i, d) in data.iter().enumerate() {
         if d.bDeviceIsConnected > 0 {
             out.count = i + 1;
         }
-        out.poses[i].index = i;
-        out.poses[i].is_connected = d.bDeviceIsConnected > 0;
-        out.poses[i].is_valid = d.bPoseIsValid > 0;
-        out.poses[i].to_device = d.mDeviceToAbsoluteTracking.m;
-        out.poses[i].velocity = d.vVelocity.v;
-        out.poses[i].angular_velocity = d.vAngularVelocity.v;
+        out.poses[i] = TrackedDevicePose::from_raw(i, *d);
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracking_universe_origin_round_trips() {
+        for &origin in &[TrackingUniverseOrigin::Seated, TrackingUniverseOrigin::Standing, TrackingUniverseOrigin::RawAndUncalibrated] {
+            assert_eq!(TrackingUniverseOrigin::from_raw(origin.to_raw()), origin);
+
+            let raw: openvr_sys::ETrackingUniverseOrigin = origin.into();
+            assert_eq!(TrackingUniverseOrigin::from(raw), origin);
+        }
+    }
+
+    #[test]
+    fn button_masks_are_single_bits_at_the_id() {
+        assert_eq!(Button::System.mask(), 1);
+        assert_eq!(Button::Grip.mask(), 1 << 2);
+        assert_eq!(Button::ProximitySensor.mask(), 1 << 31);
+        assert_eq!(Button::Axis1.mask(), 1 << 33);
+        assert_eq!(Button::Axis4.mask(), 1 << 36);
+    }
+
+    #[test]
+    fn controller_state_reads_the_masks() {
+        let mut state = ControllerState::from_raw(unsafe { ::std::mem::zeroed() });
+        state.pressed_mask = Button::Axis1.mask();
+        state.touched_mask = Button::Axis0.mask() | Button::Axis1.mask();
+
+        assert!(state.button_pressed(Button::Axis1));
+        assert!(!state.button_pressed(Button::Axis0));
+        assert!(state.button_touched(Button::Axis0));
+        assert!(!state.button_touched(Button::Grip));
+    }
+
+    fn pose(to_device: [[f32; 4]; 3]) -> TrackedDevicePose {
+        TrackedDevicePose {
+            index: 0,
+            to_device,
+            velocity: [0.0; 3],
+            angular_velocity: [0.0; 3],
+            is_valid: true,
+            is_connected: true,
+            tracking_result: TrackingResult::RunningOk,
+        }
+    }
+
+    // builds yaw * pitch * roll, the order documented on euler_angles
+    fn rotation(yaw: f32, pitch: f32, roll: f32) -> [[f32; 4]; 3] {
+        let (sy, cy) = yaw.sin_cos();
+        let (sp, cp) = pitch.sin_cos();
+        let (sr, cr) = roll.sin_cos();
+        let y = [[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]];
+        let p = [[1.0, 0.0, 0.0], [0.0, cp, -sp], [0.0, sp, cp]];
+        let r = [[cr, -sr, 0.0], [sr, cr, 0.0], [0.0, 0.0, 1.0]];
+
+        let mul = |a: [[f32; 3]; 3], b: [[f32; 3]; 3]| {
+            let mut out = [[0.0; 3]; 3];
+            for row in 0..3 {
+                for column in 0..3 {
+                    out[row][column] = (0..3).map(|i| a[row][i] * b[i][column]).sum();
+                }
+            }
+            out
+        };
+        let m = mul(mul(y, p), r);
+
+        [
+            [m[0][0], m[0][1], m[0][2], 0.0],
+            [m[1][0], m[1][1], m[1][2], 0.0],
+            [m[2][0], m[2][1], m[2][2], 0.0],
+        ]
+    }
+
+    fn assert_close(a: (f32, f32, f32), b: (f32, f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4 && (a.2 - b.2).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn position_is_the_translation_column() {
+        let mut m = rotation(0.3, 0.2, 0.1);
+        m[0][3] = 1.0;
+        m[1][3] = -2.0;
+        m[2][3] = 3.5;
+        assert_eq!(pose(m).position(), [1.0, -2.0, 3.5]);
+    }
+
+    #[test]
+    fn euler_angles_of_identity_are_zero() {
+        assert_close(pose(rotation(0.0, 0.0, 0.0)).euler_angles(), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn euler_angles_of_a_quarter_yaw() {
+        use std::f32::consts::FRAC_PI_2;
+
+        let m = pose(rotation(FRAC_PI_2, 0.0, 0.0));
+        // turning left by 90 degrees makes the forward direction (-z) point to -x
+        assert!((m.to_device[0][2] - 1.0).abs() < 1e-6);
+        assert_close(m.euler_angles(), (FRAC_PI_2, 0.0, 0.0));
+    }
+
+    #[test]
+    fn euler_angles_round_trip() {
+        for &angles in &[(0.5, 0.3, -0.2), (-2.0, -1.0, 1.5), (3.0, 0.1, -3.0)] {
+            let (yaw, pitch, roll) = pose(rotation(angles.0, angles.1, angles.2)).euler_angles();
+            assert_close((yaw, pitch, roll), angles);
+        }
+    }
+
+    #[test]
+    fn euler_angles_at_gimbal_lock_rebuild_the_rotation() {
+        use std::f32::consts::FRAC_PI_2;
+
+        // straight up or down yaw and roll can not be told apart, roll is folded into yaw
+        for &pitch in &[FRAC_PI_2, -FRAC_PI_2] {
+            let m = rotation(0.7, pitch, 0.4);
+            let (yaw, found_pitch, roll) = pose(m).euler_angles();
+
+            assert_eq!(roll, 0.0);
+            let rebuilt = rotation(yaw, found_pitch, roll);
+            for row in 0..3 {
+                for column in 0..3 {
+                    assert!((rebuilt[row][column] - m[row][column]).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn poses_and_controller_states_round_trip_through_serde() {
+        use serde_json;
+
+        let mut m = rotation(0.5, 0.3, -0.2);
+        m[1][3] = 1.25;
+        let original = pose(m);
+        let json = serde_json::to_string(&original).unwrap();
+        let parsed: TrackedDevicePose = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.to_device, original.to_device);
+        assert_eq!(parsed.tracking_result(), original.tracking_result());
+        assert_eq!(parsed.is_valid(), original.is_valid());
+
+        let mut state = ControllerState::from_raw(unsafe { ::std::mem::zeroed() });
+        state.packet_num = 7;
+        state.pressed_mask = Button::Grip.mask();
+        state.axes[1] = [0.5, -0.25];
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: ControllerState = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.packet_num, 7);
+        assert_eq!(parsed.pressed_mask, state.pressed_mask);
+        assert_eq!(parsed.axes, state.axes);
+    }
+
+    #[test]
+    fn packet_number_detects_changes() {
+        let mut raw: openvr_sys::VRControllerState_t = unsafe { ::std::mem::zeroed() };
+        raw.unPacketNum = 41;
+        let state = ControllerState::from_raw(raw);
+
+        assert_eq!(state.packet_number(), 41);
+        assert!(!state.has_changed_since(41));
+        assert!(state.has_changed_since(40));
+
+        // the counter wraps around, the step from u32::MAX to 0 still counts as a change
+        raw.unPacketNum = 0;
+        assert!(ControllerState::from_raw(raw).has_changed_since(u32::max_value()));
+    }
+
+    #[test]
+    fn tracking_results_map_to_their_variants() {
+        use openvr_sys::ETrackingResult::*;
+
+        let cases = [
+            (ETrackingResult_TrackingResult_Uninitialized, TrackingResult::Uninitialized),
+            (ETrackingResult_TrackingResult_Calibrating_InProgress, TrackingResult::CalibratingInProgress),
+            (ETrackingResult_TrackingResult_Calibrating_OutOfRange, TrackingResult::CalibratingOutOfRange),
+            (ETrackingResult_TrackingResult_Running_OK, TrackingResult::RunningOk),
+            (ETrackingResult_TrackingResult_Running_OutOfRange, TrackingResult::RunningOutOfRange),
+        ];
+
+        for &(raw, result) in cases.iter() {
+            assert_eq!(TrackingResult::from_raw(raw), result);
+
+            let mut data: openvr_sys::TrackedDevicePose_t = unsafe { ::std::mem::zeroed() };
+            data.eTrackingResult = raw;
+            data.bDeviceIsConnected = 1;
+            let pose = TrackedDevicePose::from_raw(3, data);
+            assert_eq!(pose.tracking_result(), result);
+            assert!(pose.is_connected() && !pose.is_valid());
+        }
+    }
+}